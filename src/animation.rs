@@ -1,60 +1,190 @@
-use std::time::Duration;
+use std::{
+    collections::BTreeMap,
+    ops::RangeInclusive,
+    time::{Duration, TryFromFloatSecsError},
+};
 
 use bevy::prelude::*;
+use serde::Deserialize;
 
-#[derive(Component)]
-pub struct AnimationTimer {
-    frames: u32,
-    timer: Timer,
-    current_frame: u32,
+#[derive(SystemSet, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct AnimationSystem;
+
+/// How a clip carries on once it reaches its last frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum PlaybackMode {
+    /// Starts again from the first frame
+    Loop,
+    /// Holds the last frame and sends `AnimationFinished`
+    Once,
+    /// Plays backwards to the first frame, then forwards again
+    PingPong,
 }
 
-/// Represent a direction that a sprite is facing
-#[derive(Component, Default, Eq, PartialEq)]
-pub enum Direction {
-    #[default]
-    Forward,
-    Backward,
+/// A run of frames in a sprite sheet
+#[derive(Clone, PartialEq, Debug)]
+pub struct AnimationClip {
+    /// Atlas indices of the first and last frames
+    first: usize,
+    last: usize,
+    frame_duration: Duration,
+    mode: PlaybackMode,
+}
+
+impl AnimationClip {
+    /// Fails if `fps` is zero, negative or NaN, since frames would never change
+    pub fn new(frames: RangeInclusive<usize>, fps: f32, mode: PlaybackMode) -> Result<Self, TryFromFloatSecsError> {
+        Ok(Self {
+            first: *frames.start(),
+            last: *frames.end().max(frames.start()),
+            frame_duration: Duration::try_from_secs_f32(1.0 / fps)?,
+            mode,
+        })
+    }
+}
+
+/// Clips available to an entity, by name
+#[derive(Clone, Default, Debug)]
+pub struct AnimationClips(BTreeMap<String, AnimationClip>);
+
+impl AnimationClips {
+    pub fn with(mut self, name: &str, clip: AnimationClip) -> Self {
+        self.0.insert(name.to_owned(), clip);
+        self
+    }
+}
+
+/// Sent when a `PlaybackMode::Once` clip reaches its last frame
+#[derive(Event, Clone, Debug)]
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub clip: String,
+}
+
+/// Plays named clips on a texture atlas sprite
+#[derive(Component)]
+pub struct Animator {
+    clips: AnimationClips,
+    current: String,
+    frame: usize,
+    /// Time since the current frame started
+    elapsed: Duration,
+    /// Direction through the frames, only ever false for `PlaybackMode::PingPong`
+    forward: bool,
+    finished: bool,
 }
 
-impl AnimationTimer {
-    pub fn new(frames: u32, total_secs: f32) -> Self {
-        let duration = Duration::from_secs_f32(total_secs / (frames as f32));
-        Self {
-            frames,
-            timer: Timer::new(duration, TimerMode::Repeating),
-            current_frame: 0,
+impl Animator {
+    pub fn new(clips: AnimationClips, clip: &str) -> Self {
+        let mut animator = Self {
+            clips,
+            current: String::new(),
+            frame: 0,
+            elapsed: Duration::ZERO,
+            forward: true,
+            finished: false,
+        };
+        animator.restart(clip);
+        animator
+    }
+
+    pub fn current(&self) -> &str {
+        &self.current
+    }
+
+    /// Atlas index to show
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Switches to `clip`, unless it is already playing
+    pub fn play(&mut self, clip: &str) {
+        if self.current != clip {
+            self.restart(clip);
         }
     }
 
-    pub fn start(&mut self) {
-        self.timer.reset();
-        self.timer.unpause();
-        self.current_frame = 0;
+    /// Plays `clip` from its first frame, even if it is already playing.
+    /// A clip that doesn't exist holds the current frame, so a sheet can leave out clips it has no art for
+    pub fn restart(&mut self, clip: &str) {
+        self.current = clip.to_owned();
+        self.elapsed = Duration::ZERO;
+        self.forward = true;
+        self.finished = false;
+        match self.clips.0.get(clip) {
+            Some(clip) => self.frame = clip.first,
+            // Clips are empty until the sprite sheet has loaded
+            None if !self.clips.0.is_empty() => warn_once!("No animation clip named {clip}"),
+            None => {}
+        }
     }
 
-    /// Resets and stops the animation
-    pub fn stop(&mut self) {
-        self.timer.reset();
-        self.timer.pause();
-        self.current_frame = 0;
+    /// Swaps in a new set of clips, such as from a reloaded sprite sheet, and restarts the current clip from them
+    pub fn set_clips(&mut self, clips: AnimationClips) {
+        self.clips = clips;
+        let current = std::mem::take(&mut self.current);
+        self.restart(&current);
     }
 
-    fn get_current_frame(&mut self, tick: Duration) -> u32 {
-        self.timer.tick(tick);
-        self.current_frame += self.timer.times_finished_this_tick();
-        if self.current_frame >= self.frames {
-            self.timer.pause();
-            self.current_frame = 0;
+    /// Moves through the current clip, returning true if it finished during `delta`
+    fn advance(&mut self, delta: Duration) -> bool {
+        let Some(clip) = self.clips.0.get(&self.current) else {
+            return false;
+        };
+        if self.finished || clip.frame_duration.is_zero() {
+            return false;
         }
-        self.current_frame
+        self.elapsed += delta;
+        while self.elapsed >= clip.frame_duration {
+            self.elapsed -= clip.frame_duration;
+            match clip.mode {
+                PlaybackMode::Loop => {
+                    self.frame = if self.frame >= clip.last { clip.first } else { self.frame + 1 };
+                }
+                PlaybackMode::Once => {
+                    if self.frame >= clip.last {
+                        self.finished = true;
+                        return true;
+                    }
+                    self.frame += 1;
+                }
+                PlaybackMode::PingPong => {
+                    if clip.first == clip.last {
+                        continue;
+                    }
+                    if self.forward && self.frame >= clip.last || !self.forward && self.frame <= clip.first {
+                        self.forward = !self.forward;
+                    }
+                    self.frame = if self.forward { self.frame + 1 } else { self.frame - 1 };
+                }
+            }
+        }
+        false
     }
 }
 
-pub fn animate_sprites(mut query: Query<(&mut Sprite, &mut AnimationTimer)>, time: Res<Time>) {
-    for (mut sprite, mut animation_timer) in query.iter_mut() {
+/// Represent a direction that a sprite is facing
+#[derive(Component, Default, Eq, PartialEq)]
+pub enum Direction {
+    #[default]
+    Forward,
+    Backward,
+}
+
+pub fn animate_sprites(
+    mut query: Query<(Entity, &mut Sprite, &mut Animator)>,
+    mut writer: EventWriter<AnimationFinished>,
+    time: Res<Time>,
+) {
+    for (entity, mut sprite, mut animator) in query.iter_mut() {
+        if animator.advance(time.delta()) {
+            writer.send(AnimationFinished {
+                entity,
+                clip: animator.current.clone(),
+            });
+        }
         if let Some(atlas) = &mut sprite.texture_atlas {
-            atlas.index = animation_timer.get_current_frame(time.delta()) as usize;
+            atlas.index = animator.frame;
         }
     }
 }
@@ -64,3 +194,53 @@ pub fn direct_sprites(mut query: Query<(&mut Sprite, &Direction)>) {
         sprite.flip_x = *direction == Direction::Backward;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames_over(animator: &mut Animator, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                animator.advance(Duration::from_secs_f32(0.1));
+                animator.frame()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clips_follow_their_mode() {
+        let clips = AnimationClips::default()
+            .with("loop", AnimationClip::new(2..=4, 10.0, PlaybackMode::Loop).unwrap())
+            .with("once", AnimationClip::new(0..=2, 10.0, PlaybackMode::Once).unwrap())
+            .with("ping_pong", AnimationClip::new(5..=7, 10.0, PlaybackMode::PingPong).unwrap());
+        let mut animator = Animator::new(clips, "loop");
+        assert_eq!(animator.frame(), 2);
+        assert_eq!(frames_over(&mut animator, 4), [3, 4, 2, 3]);
+
+        animator.play("ping_pong");
+        assert_eq!(frames_over(&mut animator, 5), [6, 7, 6, 5, 6]);
+
+        animator.play("once");
+        assert_eq!(frames_over(&mut animator, 2), [1, 2]);
+        assert!(animator.advance(Duration::from_secs_f32(0.1)));
+        assert_eq!(animator.frame(), 2);
+        // Only reported the once
+        assert!(!animator.advance(Duration::from_secs_f32(0.1)));
+    }
+
+    #[test]
+    fn play_keeps_current_clip_going() {
+        let clips = AnimationClips::default().with("fly", AnimationClip::new(0..=3, 10.0, PlaybackMode::Loop).unwrap());
+        let mut animator = Animator::new(clips, "fly");
+        frames_over(&mut animator, 2);
+        animator.play("fly");
+        assert_eq!(animator.frame(), 2);
+        animator.restart("fly");
+        assert_eq!(animator.frame(), 0);
+        // Missing clips hold the frame rather than playing something else
+        animator.play("missing");
+        frames_over(&mut animator, 2);
+        assert_eq!(animator.frame(), 0);
+    }
+}
@@ -1,10 +1,27 @@
-use bevy::prelude::*;
+use std::{collections::BTreeMap, ops::Range, time::Duration};
+
+use bevy::{audio::Volume, prelude::*};
+use rand::Rng;
 
 use crate::{
     game::EnemyEaten,
-    player::{PlayerFlapped, PlayerScreetched},
+    loading::LoadingAssets,
+    pause_menu::PausedState,
+    player::{Player, PlayerFlapped, PlayerScreetched},
+    settings::Settings,
+    GameState,
 };
 
+pub use mixer::{AudioBus, Mixer};
+pub use music::MusicPlugin;
+
+mod mixer;
+mod music;
+mod panning;
+
+/// Seconds between an insect's buzzes, picked at random from the range so insects don't buzz in step
+const BUZZ_INTERVAL_SECS: Range<f32> = 0.6..1.2;
+
 #[derive(SystemSet, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct SoundEffectSystem;
 
@@ -13,24 +30,159 @@ pub struct SoundPlugin;
 impl Plugin for SoundPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SoundHandler>();
-        app.add_systems(Startup, load_sounds);
+        app.init_resource::<Mixer>();
+        app.add_event::<PlaySound>();
+        app.add_systems(Startup, (load_sounds, panning::spawn_listener));
         app.add_systems(
             Update,
             (
-                play_flap.run_if(on_event::<PlayerFlapped>),
-                play_screetch.run_if(on_event::<PlayerScreetched>),
-                play_munch.run_if(on_event::<EnemyEaten>),
+                play_at_player::<PlayerFlapped>,
+                play_at_player::<PlayerScreetched>,
+                play_munch,
+                play_buzzes
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(in_state(PausedState::Unpaused)),
             )
-                .in_set(SoundEffectSystem),
+                .before(SoundEffectSystem),
         );
+        app.add_systems(Update, play_sounds.run_if(on_event::<PlaySound>).in_set(SoundEffectSystem));
+        app.add_systems(OnEnter(PausedState::Paused), mixer::duck_for_pause);
+        app.add_systems(OnExit(PausedState::Paused), mixer::unduck_after_pause);
+        app.add_systems(
+            PostUpdate,
+            (
+                mixer::apply_settings.run_if(resource_changed::<Settings>),
+                mixer::update_playing_volumes.run_if(resource_changed::<Mixer>),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// A sound effect that can be requested with `PlaySound`
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum SoundEffect {
+    Flap,
+    Screetch,
+    Munch,
+    Buzz,
+}
+
+impl SoundEffect {
+    pub fn bus(&self) -> AudioBus {
+        match self {
+            SoundEffect::Flap | SoundEffect::Screetch | SoundEffect::Munch | SoundEffect::Buzz => AudioBus::Sfx,
+        }
+    }
+}
+
+/// Plays a sound effect through the mixer. The only way sounds should be started
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlaySound {
+    pub sound: SoundEffect,
+    /// Where in the world the sound comes from, for panning. Played centered if `None`
+    pub position: Option<Vec2>,
+}
+
+impl PlaySound {
+    pub fn at(sound: SoundEffect, position: Vec2) -> Self {
+        Self {
+            sound,
+            position: Some(position),
+        }
+    }
+}
+
+/// How a sound effect is varied and limited, so frequent sounds don't get repetitive or pile up
+#[derive(Clone, Copy, Debug)]
+pub struct SoundConfig {
+    /// Alternate recordings, one of which is picked at random each time
+    pub clips: &'static [&'static str],
+    /// Playback speed before variation. Above 1.0 raises the pitch
+    pub speed: f32,
+    /// Volume before variation, as a fraction of the bus volume
+    pub volume: f32,
+    /// Playback speed is picked within this fraction either side of normal, which also shifts the pitch
+    pub pitch_variation: f32,
+    /// Volume is lowered by up to this fraction
+    pub volume_variation: f32,
+    /// Once this many are playing, the oldest is stopped to make room
+    pub max_voices: usize,
+    /// Requests closer together than this are dropped
+    pub min_interval_secs: f32,
+}
+
+impl SoundEffect {
+    const ALL: [SoundEffect; 4] = [SoundEffect::Flap, SoundEffect::Screetch, SoundEffect::Munch, SoundEffect::Buzz];
+
+    pub fn config(&self) -> SoundConfig {
+        match self {
+            SoundEffect::Flap => SoundConfig {
+                clips: &[SoundHandler::FLAP_SOUND_PATH],
+                speed: 1.0,
+                volume: 1.0,
+                pitch_variation: 0.08,
+                volume_variation: 0.15,
+                max_voices: 4,
+                min_interval_secs: 0.06,
+            },
+            SoundEffect::Screetch => SoundConfig {
+                clips: &[SoundHandler::SQUEAK_SOUND_PATH],
+                speed: 1.0,
+                volume: 1.0,
+                pitch_variation: 0.05,
+                volume_variation: 0.0,
+                max_voices: 2,
+                min_interval_secs: 0.1,
+            },
+            SoundEffect::Munch => SoundConfig {
+                clips: &[SoundHandler::MUNCH_SOUND_PATH],
+                speed: 1.0,
+                volume: 1.0,
+                pitch_variation: 0.12,
+                volume_variation: 0.1,
+                max_voices: 4,
+                min_interval_secs: 0.03,
+            },
+            // A wingbeat too, but smaller, faster and further away than the bat's own
+            SoundEffect::Buzz => SoundConfig {
+                clips: &[SoundHandler::FLAP_SOUND_PATH],
+                speed: 1.8,
+                volume: 0.35,
+                pitch_variation: 0.1,
+                volume_variation: 0.2,
+                max_voices: 3,
+                min_interval_secs: 0.12,
+            },
+        }
     }
 }
+
 /// Holds on to Strong Handles for Sound Effects
 #[derive(Resource, Default)]
 pub struct SoundHandler {
-    flap: Handle<AudioSource>,
-    munch: Handle<AudioSource>,
-    screetch: Handle<AudioSource>,
+    banks: BTreeMap<SoundEffect, SoundBank>,
+}
+
+/// Loaded clips for one sound effect, and when it last played
+#[derive(Default)]
+struct SoundBank {
+    clips: Vec<Handle<AudioSource>>,
+    last_played: Option<Duration>,
+}
+
+impl SoundBank {
+    /// Whether the sound may play at `now`, given its `min_interval_secs`. Records it as played if so
+    fn trigger(&mut self, now: Duration, config: &SoundConfig) -> bool {
+        if self
+            .last_played
+            .is_some_and(|last| now.saturating_sub(last).as_secs_f32() < config.min_interval_secs)
+        {
+            return false;
+        }
+        self.last_played = Some(now);
+        true
+    }
 }
 
 impl SoundHandler {
@@ -39,37 +191,199 @@ impl SoundHandler {
     const SQUEAK_SOUND_PATH: &str = "sounds/squeak.ogg";
 
     fn load(&mut self, asset_server: Res<AssetServer>) {
-        *self = Self {
-            flap: asset_server.load(Self::FLAP_SOUND_PATH),
-            munch: asset_server.load(Self::MUNCH_SOUND_PATH),
-            screetch: asset_server.load(Self::SQUEAK_SOUND_PATH),
-        }
+        self.banks = SoundEffect::ALL
+            .into_iter()
+            .map(|sound| {
+                let clips = sound.config().clips.iter().map(|path| asset_server.load(*path)).collect();
+                (sound, SoundBank { clips, last_played: None })
+            })
+            .collect();
     }
 }
 
-pub fn load_sounds(asset_server: Res<AssetServer>, mut sound_handler: ResMut<SoundHandler>) {
+pub fn load_sounds(asset_server: Res<AssetServer>, mut sound_handler: ResMut<SoundHandler>, mut loading: ResMut<LoadingAssets>) {
     sound_handler.load(asset_server);
+    for handle in sound_handler.banks.values().flat_map(|bank| bank.clips.iter()) {
+        loading.track(handle);
+    }
 }
-pub fn play_flap(mut commands: Commands, sound_handler: Res<SoundHandler>) {
-    commands.spawn((AudioPlayer::new(sound_handler.flap.clone()), PlaybackSettings::DESPAWN));
+
+/// Player event that makes a sound where the player is
+trait PlayerSound: Event {
+    const SOUND: SoundEffect;
+}
+
+impl PlayerSound for PlayerFlapped {
+    const SOUND: SoundEffect = SoundEffect::Flap;
+}
+
+impl PlayerSound for PlayerScreetched {
+    const SOUND: SoundEffect = SoundEffect::Screetch;
+}
+
+fn play_at_player<E: PlayerSound>(
+    mut events: EventReader<E>,
+    mut sounds: EventWriter<PlaySound>,
+    player: Option<Single<&Transform, With<Player>>>,
+) {
+    let position = player.map(|transform| transform.translation.truncate());
+    for _ in events.read() {
+        sounds.send(PlaySound { sound: E::SOUND, position });
+    }
+}
+
+fn play_munch(mut events: EventReader<EnemyEaten>, mut sounds: EventWriter<PlaySound>) {
+    for event in events.read() {
+        sounds.send(PlaySound::at(SoundEffect::Munch, event.position));
+    }
+}
+
+/// Makes an insect buzz every so often from wherever it is, so it can be heard before it is seen
+#[derive(Component)]
+pub struct Buzzing(Timer);
+
+impl Default for Buzzing {
+    /// The first buzz comes sooner than the rest, so insects are heard soon after spawning
+    fn default() -> Self {
+        let secs = rand::thread_rng().gen_range(0.0..BUZZ_INTERVAL_SECS.start);
+        Self(Timer::from_seconds(secs, TimerMode::Once))
+    }
+}
+
+fn play_buzzes(time: Res<Time>, mut sounds: EventWriter<PlaySound>, mut insects: Query<(&Transform, &mut Buzzing)>) {
+    // Cosmetic, like sound variation, so `GameRng` is left alone
+    let mut rng = rand::thread_rng();
+    for (transform, mut buzzing) in insects.iter_mut() {
+        if buzzing.0.tick(time.delta()).just_finished() {
+            sounds.send(PlaySound::at(SoundEffect::Buzz, transform.translation.truncate()));
+            buzzing.0 = Timer::from_seconds(rng.gen_range(BUZZ_INTERVAL_SECS), TimerMode::Once);
+        }
+    }
 }
-pub fn play_screetch(mut commands: Commands, sound_handler: Res<SoundHandler>) {
-    commands.spawn((AudioPlayer::new(sound_handler.screetch.clone()), PlaybackSettings::DESPAWN));
+
+/// A playing sound effect, kept track of to limit how many play at once
+#[derive(Component)]
+struct SoundVoice {
+    sound: SoundEffect,
+    started: Duration,
 }
-pub fn play_munch(mut commands: Commands, sound_handler: Res<SoundHandler>) {
-    commands.spawn((AudioPlayer::new(sound_handler.munch.clone()), PlaybackSettings::DESPAWN));
+
+fn play_sounds(
+    mut commands: Commands,
+    mut events: EventReader<PlaySound>,
+    mut sound_handler: ResMut<SoundHandler>,
+    mixer: Res<Mixer>,
+    time: Res<Time<Real>>,
+    voices: Query<(Entity, &SoundVoice)>,
+) {
+    // Variation is cosmetic, so it comes from its own generator and leaves `GameRng` alone for replays
+    let mut rng = rand::thread_rng();
+    let now = time.elapsed();
+    let mut playing: Vec<(Entity, SoundEffect, Duration)> =
+        voices.iter().map(|(entity, voice)| (entity, voice.sound, voice.started)).collect();
+    for event in events.read() {
+        let config = event.sound.config();
+        let Some(bank) = sound_handler.banks.get_mut(&event.sound) else {
+            continue;
+        };
+        if bank.clips.is_empty() || !bank.trigger(now, &config) {
+            continue;
+        }
+
+        // Steal the oldest voices of this sound until there is room for the new one
+        while playing.iter().filter(|(_, sound, _)| *sound == event.sound).count() >= config.max_voices {
+            let oldest = playing
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, sound, _))| *sound == event.sound)
+                .min_by_key(|(_, (_, _, started))| *started)
+                .map(|(index, _)| index);
+            let Some(index) = oldest else {
+                break;
+            };
+            let (entity, _, _) = playing.swap_remove(index);
+            commands.entity(entity).despawn();
+        }
+
+        let clip = bank.clips[rng.gen_range(0..bank.clips.len())].clone();
+        let speed = config.speed * (1.0 + rng.gen_range(-config.pitch_variation..=config.pitch_variation));
+        let mut gain = config.volume * (1.0 - rng.gen_range(0.0..=config.volume_variation));
+        let mut settings = PlaybackSettings::DESPAWN.with_speed(speed);
+        let mut translation = Vec3::ZERO;
+        if let Some(position) = event.position {
+            gain *= panning::offscreen_gain(position);
+            settings = settings.with_spatial(true).with_spatial_scale(panning::spatial_scale());
+            translation = panning::emitter_translation(position);
+        }
+        let bus = event.sound.bus();
+        let entity = commands
+            .spawn((
+                AudioPlayer::new(clip),
+                settings.with_volume(Volume::new(mixer.volume(bus) * gain)),
+                Transform::from_translation(translation),
+                mixer::OnBus { bus, gain },
+                SoundVoice {
+                    sound: event.sound,
+                    started: now,
+                },
+            ))
+            .id();
+        playing.push((entity, event.sound, now));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::WINDOW_DIMENSIONS;
     use std::path::PathBuf;
 
     #[test]
     fn files_present() {
-        assert_asset_present(SoundHandler::FLAP_SOUND_PATH);
-        assert_asset_present(SoundHandler::SQUEAK_SOUND_PATH);
-        assert_asset_present(SoundHandler::MUNCH_SOUND_PATH);
+        for sound in SoundEffect::ALL {
+            for clip in sound.config().clips {
+                assert_asset_present(clip);
+            }
+        }
+    }
+
+    #[test]
+    fn repeats_within_interval_are_dropped() {
+        let config = SoundEffect::Flap.config();
+        let mut bank = SoundBank::default();
+        assert!(bank.trigger(Duration::from_secs(1), &config));
+        assert!(!bank.trigger(Duration::from_secs_f32(1.0 + config.min_interval_secs / 2.0), &config));
+        assert!(bank.trigger(Duration::from_secs_f32(1.0 + config.min_interval_secs * 2.0), &config));
+    }
+
+    #[test]
+    fn insects_buzz_quieter_once_past_the_edge() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_event::<PlaySound>();
+        app.add_systems(Update, play_buzzes);
+        let edge = WINDOW_DIMENSIONS.x / 2.0;
+        for x in [edge - 20.0, edge + 200.0] {
+            app.world_mut()
+                .spawn((Buzzing(Timer::from_seconds(0.0, TimerMode::Once)), Transform::from_xyz(x, 0.0, 0.0)));
+        }
+        app.update();
+
+        let mut buzzes: Vec<Vec2> = app
+            .world_mut()
+            .resource_mut::<Events<PlaySound>>()
+            .drain()
+            .filter_map(|event| event.position)
+            .collect();
+        buzzes.sort_by(|a, b| a.x.total_cmp(&b.x));
+        let [near, past] = buzzes[..] else {
+            panic!("expected a buzz from each insect, got {buzzes:?}");
+        };
+        assert_eq!(panning::offscreen_gain(near), 1.0);
+        assert!(panning::offscreen_gain(past) < 1.0);
+        // Both come from the right, the one past the edge from as far right as sounds go
+        assert!(panning::emitter_translation(near).x > 0.0);
+        assert_eq!(panning::emitter_translation(past).x, edge);
     }
 
     fn assert_asset_present(asset_path: &str) {
@@ -0,0 +1,133 @@
+//! Volume and mute for each group of sounds, applied to new and already playing sounds alike
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+
+/// How loud sound effects and music stay while the game is paused
+const PAUSED_SFX_DUCK: f32 = 0.3;
+const PAUSED_MUSIC_DUCK: f32 = 0.4;
+
+/// Group of sounds that share a volume. Everything also goes through `Master`
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum AudioBus {
+    Master,
+    Sfx,
+    Music,
+    Ui,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bus {
+    /// Between 0.0 and 1.0
+    pub volume: f32,
+    pub muted: bool,
+    /// Temporary reduction on top of `volume`, 1.0 when not ducked
+    pub duck: f32,
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+            duck: 1.0,
+        }
+    }
+}
+
+impl Bus {
+    fn level(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume * self.duck
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Mixer {
+    buses: BTreeMap<AudioBus, Bus>,
+}
+
+impl Mixer {
+    pub fn bus(&self, bus: AudioBus) -> Bus {
+        self.buses.get(&bus).copied().unwrap_or_default()
+    }
+
+    pub fn bus_mut(&mut self, bus: AudioBus) -> &mut Bus {
+        self.buses.entry(bus).or_default()
+    }
+
+    /// Volume a sound on `bus` should play at, including the master bus
+    pub fn volume(&self, bus: AudioBus) -> f32 {
+        let master = self.bus(AudioBus::Master).level();
+        if bus == AudioBus::Master {
+            master
+        } else {
+            master * self.bus(bus).level()
+        }
+    }
+}
+
+/// Marks a playing sound with the bus it belongs to, so volume changes reach it
+#[derive(Component, Clone, Copy)]
+pub struct OnBus {
+    pub bus: AudioBus,
+    /// This sound's own volume, relative to the bus
+    pub gain: f32,
+}
+
+pub fn apply_settings(settings: Res<Settings>, mut mixer: ResMut<Mixer>) {
+    let volumes = [
+        (AudioBus::Master, settings.master_volume, settings.master_muted),
+        (AudioBus::Sfx, settings.sfx_volume, settings.sfx_muted),
+        (AudioBus::Music, settings.music_volume, settings.music_muted),
+        // Menu sounds are effects as far as the player is concerned
+        (AudioBus::Ui, settings.sfx_volume, settings.sfx_muted),
+    ];
+    for (bus, volume, muted) in volumes {
+        let bus = mixer.bus_mut(bus);
+        bus.volume = volume;
+        bus.muted = muted;
+    }
+}
+
+pub fn duck_for_pause(mut mixer: ResMut<Mixer>) {
+    mixer.bus_mut(AudioBus::Sfx).duck = PAUSED_SFX_DUCK;
+    mixer.bus_mut(AudioBus::Music).duck = PAUSED_MUSIC_DUCK;
+}
+
+pub fn unduck_after_pause(mut mixer: ResMut<Mixer>) {
+    mixer.bus_mut(AudioBus::Sfx).duck = 1.0;
+    mixer.bus_mut(AudioBus::Music).duck = 1.0;
+}
+
+pub fn update_playing_volumes(mixer: Res<Mixer>, sinks: Query<(&OnBus, &AudioSink)>, spatial_sinks: Query<(&OnBus, &SpatialAudioSink)>) {
+    for (bus, sink) in sinks.iter() {
+        sink.set_volume(mixer.volume(bus.bus) * bus.gain);
+    }
+    for (bus, sink) in spatial_sinks.iter() {
+        sink.set_volume(mixer.volume(bus.bus) * bus.gain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buses_combine_with_master() {
+        let mut mixer = Mixer::default();
+        mixer.bus_mut(AudioBus::Master).volume = 0.5;
+        mixer.bus_mut(AudioBus::Sfx).volume = 0.5;
+        mixer.bus_mut(AudioBus::Sfx).duck = 0.5;
+        assert_eq!(mixer.volume(AudioBus::Sfx), 0.125);
+        assert_eq!(mixer.volume(AudioBus::Music), 0.5);
+
+        mixer.bus_mut(AudioBus::Master).muted = true;
+        assert_eq!(mixer.volume(AudioBus::Music), 0.0);
+    }
+}
@@ -0,0 +1,157 @@
+//! Looping music for each game state, crossfaded on state changes.
+//!
+//! Every track file is optional (see `assets/asset_distribution_note.md`), a missing one is just silent
+use bevy::{audio::Volume, prelude::*};
+
+use super::{AudioBus, Mixer};
+use crate::{lighting::WorldLight, loading::LoadingAssets, GameState};
+
+/// Time taken for one track to fade out and the next to fade in
+const CROSSFADE_SECS: f32 = 1.5;
+
+pub struct MusicPlugin;
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicHandles>();
+        app.add_systems(Startup, load_music);
+        app.add_systems(Update, (switch_track.run_if(state_changed::<GameState>), fade_music).chain());
+    }
+}
+
+/// Which music fits the current state
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum MusicTrack {
+    Menu,
+    Playing,
+    GameOver,
+}
+
+impl MusicTrack {
+    fn for_state(state: GameState) -> Option<Self> {
+        match state {
+            GameState::Loading => None,
+            GameState::MainMenu => Some(MusicTrack::Menu),
+            GameState::Ready | GameState::Playing => Some(MusicTrack::Playing),
+            GameState::Gameover => Some(MusicTrack::GameOver),
+        }
+    }
+}
+
+/// Part of a track. Each layer is its own file, and all of them play in sync
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum MusicLayer {
+    Base,
+    /// Faded in as the world darkens, to build tension as the run goes on
+    Intensity,
+}
+
+#[derive(Resource, Default)]
+struct MusicHandles {
+    menu: Handle<AudioSource>,
+    playing: Handle<AudioSource>,
+    playing_intensity: Handle<AudioSource>,
+    game_over: Handle<AudioSource>,
+}
+
+impl MusicHandles {
+    const MENU_PATH: &str = "music/menu.ogg";
+    const PLAYING_PATH: &str = "music/playing.ogg";
+    const PLAYING_INTENSITY_PATH: &str = "music/playing_intensity.ogg";
+    const GAME_OVER_PATH: &str = "music/game_over.ogg";
+
+    /// Layers that make up `track`, with their files
+    fn layers(&self, track: MusicTrack) -> Vec<(MusicLayer, Handle<AudioSource>)> {
+        match track {
+            MusicTrack::Menu => vec![(MusicLayer::Base, self.menu.clone())],
+            MusicTrack::Playing => vec![
+                (MusicLayer::Base, self.playing.clone()),
+                (MusicLayer::Intensity, self.playing_intensity.clone()),
+            ],
+            MusicTrack::GameOver => vec![(MusicLayer::Base, self.game_over.clone())],
+        }
+    }
+}
+
+fn load_music(asset_server: Res<AssetServer>, mut handles: ResMut<MusicHandles>, mut loading: ResMut<LoadingAssets>) {
+    *handles = MusicHandles {
+        menu: asset_server.load(MusicHandles::MENU_PATH),
+        playing: asset_server.load(MusicHandles::PLAYING_PATH),
+        playing_intensity: asset_server.load(MusicHandles::PLAYING_INTENSITY_PATH),
+        game_over: asset_server.load(MusicHandles::GAME_OVER_PATH),
+    };
+    for handle in [&handles.menu, &handles.playing, &handles.playing_intensity, &handles.game_over] {
+        loading.track_optional(handle);
+    }
+}
+
+/// One layer of a playing track
+#[derive(Component)]
+struct MusicVoice {
+    track: MusicTrack,
+    layer: MusicLayer,
+    /// How far faded in, between 0.0 and 1.0
+    fade: f32,
+    fading_out: bool,
+}
+
+/// Fades out whatever is playing and starts the track for the new state, unless it is already playing
+fn switch_track(mut commands: Commands, state: Res<State<GameState>>, handles: Res<MusicHandles>, mut voices: Query<&mut MusicVoice>) {
+    let track = MusicTrack::for_state(*state.get());
+    let mut already_playing = false;
+    for mut voice in voices.iter_mut() {
+        if Some(voice.track) == track {
+            // Coming back to a track that was fading out picks it up where it is
+            voice.fading_out = false;
+            already_playing = true;
+        } else {
+            voice.fading_out = true;
+        }
+    }
+    let Some(track) = track.filter(|_| !already_playing) else {
+        return;
+    };
+    for (layer, handle) in handles.layers(track) {
+        commands.spawn((
+            AudioPlayer::new(handle),
+            // Silent until `fade_music` sets the volume
+            PlaybackSettings::LOOP.with_volume(Volume::new(0.0)),
+            MusicVoice {
+                track,
+                layer,
+                fade: 0.0,
+                fading_out: false,
+            },
+        ));
+    }
+}
+
+/// Moves every voice along its fade, and sets its volume from the fade, mixer and darkness.
+/// Uses real time so music keeps fading while the game is paused
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mixer: Res<Mixer>,
+    light: Option<Res<WorldLight>>,
+    mut voices: Query<(Entity, &mut MusicVoice, Option<&AudioSink>)>,
+) {
+    let step = time.delta_secs() / CROSSFADE_SECS;
+    let intensity = light.map_or(0.0, |light| 1.0 - light.level());
+    for (entity, mut voice, sink) in voices.iter_mut() {
+        if voice.fading_out {
+            voice.fade = (voice.fade - step).max(0.0);
+            if voice.fade == 0.0 {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        } else {
+            voice.fade = (voice.fade + step).min(1.0);
+        }
+        let layer_gain = match voice.layer {
+            MusicLayer::Base => 1.0,
+            MusicLayer::Intensity => intensity,
+        };
+        if let Some(sink) = sink {
+            sink.set_volume(mixer.volume(AudioBus::Music) * voice.fade * layer_gain);
+        }
+    }
+}
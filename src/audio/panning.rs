@@ -0,0 +1,58 @@
+//! Stereo panning of sound effects by where they happen across the play field
+use bevy::{
+    audio::{SpatialListener, SpatialScale},
+    prelude::*,
+};
+
+use crate::constants::WINDOW_DIMENSIONS;
+
+/// How quiet a sound gets once it is far enough off screen, so it is still a faint cue
+const MIN_OFFSCREEN_GAIN: f32 = 0.2;
+/// Distance beyond the edge of the screen over which sounds fade down to `MIN_OFFSCREEN_GAIN`
+const OFFSCREEN_FADE_DISTANCE: f32 = WINDOW_DIMENSIONS.x / 2.0;
+
+/// Maps the width of the screen onto the listener's unit gap, and ignores height so only horizontal position counts
+pub fn spatial_scale() -> SpatialScale {
+    SpatialScale(Vec3::new(1.0 / WINDOW_DIMENSIONS.x, 0.0, 0.0))
+}
+
+/// Listener in the middle of the screen, with an ear at each edge
+pub fn spawn_listener(mut commands: Commands) {
+    let edge = Vec3::X * WINDOW_DIMENSIONS.x / 2.0;
+    commands.spawn((
+        // The rodio version used by bevy 0.15 pans towards the farther ear, so the ears are swapped to put
+        // sounds on the side they happen on
+        SpatialListener {
+            left_ear_offset: edge,
+            right_ear_offset: -edge,
+        },
+        Transform::default(),
+    ));
+}
+
+/// Where a sound at `position` is played from. Held at the edge of the screen when off it, so it stays fully
+/// on that side rather than drifting back towards the middle
+pub fn emitter_translation(position: Vec2) -> Vec3 {
+    let half_width = WINDOW_DIMENSIONS.x / 2.0;
+    Vec3::new(position.x.clamp(-half_width, half_width), 0.0, 0.0)
+}
+
+/// Volume for a sound at `position`, full while on screen and fading the further off the side it is
+pub fn offscreen_gain(position: Vec2) -> f32 {
+    let beyond_edge = (position.x.abs() - WINDOW_DIMENSIONS.x / 2.0).max(0.0);
+    1.0.lerp(MIN_OFFSCREEN_GAIN, (beyond_edge / OFFSCREEN_FADE_DISTANCE).min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offscreen_sounds_fade_to_a_cue() {
+        let edge = WINDOW_DIMENSIONS.x / 2.0;
+        assert_eq!(offscreen_gain(Vec2::new(edge, 0.0)), 1.0);
+        assert!(offscreen_gain(Vec2::new(-edge - 100.0, 0.0)) < 1.0);
+        assert!((offscreen_gain(Vec2::new(edge * 10.0, 0.0)) - MIN_OFFSCREEN_GAIN).abs() < 1e-6);
+        assert_eq!(emitter_translation(Vec2::new(edge * 10.0, 50.0)), Vec3::new(edge, 0.0, 0.0));
+    }
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::constants::WINDOW_DIMENSIONS;
+use crate::{constants::WINDOW_DIMENSIONS, loading::LoadingAssets};
 
 const BACKGROUND_IMAGE_PATH: &str = "sprites/background.png";
 
@@ -11,8 +11,9 @@ impl Plugin for BackgroundPlugin {
     }
 }
 
-fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn spawn(mut commands: Commands, asset_server: Res<AssetServer>, mut loading: ResMut<LoadingAssets>) {
     let texture = asset_server.load(BACKGROUND_IMAGE_PATH);
+    loading.track(&texture);
     let mut sprite = Sprite::from_image(texture);
     sprite.custom_size = Some(WINDOW_DIMENSIONS);
     let transform = Transform::from_xyz(0.0, 0.0, -1.0);
@@ -1,21 +1,199 @@
+//! The game camera, and the shake, hit-stop and zoom punches that give gameplay moments some weight.
+//!
+//! Effects are asked for with events. Shake and zoom are scaled by `Settings::screen_shake`, so they can be turned
+//! down or off by anyone who finds them uncomfortable
 use bevy::{prelude::*, render::camera::ScalingMode};
 
-use crate::constants::WINDOW_DIMENSIONS;
+use crate::{
+    constants::WINDOW_DIMENSIONS,
+    game::EnemyEaten,
+    game_over::{GameOver, GameOverReason},
+    settings::Settings,
+};
+
+/// Trauma lost per second, so a full shake settles in about a second
+const TRAUMA_DECAY: f32 = 1.2;
+/// Offset in logical pixels and rotation in radians at full trauma
+const MAX_SHAKE_OFFSET: f32 = 24.0;
+const MAX_SHAKE_ANGLE: f32 = 0.03;
+/// How quickly the shake wobbles, in radians of its underlying waves per second
+const SHAKE_FREQUENCY: f32 = 40.0;
+/// Fraction of a zoom punch that wears off per second
+const ZOOM_PUNCH_RECOVERY: f32 = 12.0;
+/// Speed of the game clock during a hit-stop, slow enough to read as a freeze
+const HIT_STOP_SPEED: f32 = 0.05;
+
+const EAT_TRAUMA: f32 = 0.25;
+const EAT_ZOOM_PUNCH: f32 = 0.03;
+const EAT_HIT_STOP_SECS: f32 = 0.05;
+const CRASH_TRAUMA: f32 = 0.7;
+const CRASH_ZOOM_PUNCH: f32 = 0.08;
+const CRASH_HIT_STOP_SECS: f32 = 0.15;
 
 pub struct CameraPlugin;
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<ScreenShake>();
+        app.add_event::<ZoomPunch>();
+        app.add_event::<HitStop>();
+        app.init_resource::<HitStopTimer>();
         app.add_systems(Startup, spawn);
+        app.add_systems(
+            Update,
+            (
+                (punch_on_eat, punch_on_crash),
+                (start_hit_stop, end_hit_stop).chain(),
+                apply_camera_effects,
+            )
+                .chain(),
+        );
     }
 }
 
+/// Shakes the camera. Trauma from several shakes adds up, to at most 1.0
+#[derive(Event, Clone, Copy)]
+pub struct ScreenShake {
+    pub trauma: f32,
+}
+
+/// Briefly zooms the camera in by `amount`, as a fraction of the view
+#[derive(Event, Clone, Copy)]
+pub struct ZoomPunch {
+    pub amount: f32,
+}
+
+/// Nearly freezes the game for `secs`, measured in real time
+#[derive(Event, Clone, Copy)]
+pub struct HitStop {
+    pub secs: f32,
+}
+
+/// Effects currently playing on a camera. Both wear off over time
+#[derive(Component, Default)]
+struct CameraEffects {
+    /// Between 0.0 and 1.0. The shake grows with the square of it, so small knocks stay subtle
+    trauma: f32,
+    zoom_punch: f32,
+}
+
+/// Time left until the game clock goes back to full speed
+#[derive(Resource, Default)]
+struct HitStopTimer(Option<Timer>);
+
 fn spawn(mut commands: Commands) {
-    let projection = Projection::Orthographic(OrthographicProjection {
+    let projection = OrthographicProjection {
         scaling_mode: ScalingMode::Fixed {
             width: WINDOW_DIMENSIONS.x,
             height: WINDOW_DIMENSIONS.y,
         },
         ..OrthographicProjection::default_2d()
-    });
-    commands.spawn((Camera2d, projection));
+    };
+    commands.spawn((Camera2d, projection, CameraEffects::default()));
+}
+
+fn punch_on_eat(
+    mut eaten: EventReader<EnemyEaten>,
+    mut shake: EventWriter<ScreenShake>,
+    mut zoom: EventWriter<ZoomPunch>,
+    mut hit_stop: EventWriter<HitStop>,
+) {
+    for _ in eaten.read() {
+        shake.send(ScreenShake { trauma: EAT_TRAUMA });
+        zoom.send(ZoomPunch { amount: EAT_ZOOM_PUNCH });
+        hit_stop.send(HitStop { secs: EAT_HIT_STOP_SECS });
+    }
+}
+
+fn punch_on_crash(
+    mut game_over: EventReader<GameOver>,
+    mut shake: EventWriter<ScreenShake>,
+    mut zoom: EventWriter<ZoomPunch>,
+    mut hit_stop: EventWriter<HitStop>,
+) {
+    for _ in game_over.read().filter(|event| *event.reason() == GameOverReason::Crashed) {
+        shake.send(ScreenShake { trauma: CRASH_TRAUMA });
+        zoom.send(ZoomPunch { amount: CRASH_ZOOM_PUNCH });
+        hit_stop.send(HitStop { secs: CRASH_HIT_STOP_SECS });
+    }
+}
+
+/// Slows the game clock, keeping whichever hit-stop would end last
+fn start_hit_stop(mut events: EventReader<HitStop>, mut timer: ResMut<HitStopTimer>, mut time: ResMut<Time<Virtual>>) {
+    for event in events.read() {
+        let remaining = timer.0.as_ref().map_or(0.0, |timer| timer.remaining_secs());
+        if event.secs > remaining {
+            timer.0 = Some(Timer::from_seconds(event.secs, TimerMode::Once));
+            time.set_relative_speed(HIT_STOP_SPEED);
+        }
+    }
+}
+
+/// Puts the game clock back to full speed once the hit-stop is over. Uses real time, since game time is what stopped
+fn end_hit_stop(real_time: Res<Time<Real>>, mut timer: ResMut<HitStopTimer>, mut time: ResMut<Time<Virtual>>) {
+    let Some(hit_stop) = timer.0.as_mut() else {
+        return;
+    };
+    if hit_stop.tick(real_time.delta()).finished() {
+        timer.0 = None;
+        time.set_relative_speed(1.0);
+    }
+}
+
+/// Moves, turns and zooms the camera from its effects, and lets them wear off.
+/// Uses real time so the camera keeps settling during a hit-stop
+fn apply_camera_effects(
+    mut shakes: EventReader<ScreenShake>,
+    mut zooms: EventReader<ZoomPunch>,
+    real_time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    camera: Single<(&mut CameraEffects, &mut Transform, &mut OrthographicProjection)>,
+) {
+    let (mut effects, mut transform, mut projection) = camera.into_inner();
+    let delta = real_time.delta_secs();
+    effects.trauma = (effects.trauma - TRAUMA_DECAY * delta).max(0.0);
+    effects.zoom_punch *= (1.0 - ZOOM_PUNCH_RECOVERY * delta).max(0.0);
+    for shake in shakes.read() {
+        effects.trauma = (effects.trauma + shake.trauma).min(1.0);
+    }
+    for zoom in zooms.read() {
+        effects.zoom_punch = effects.zoom_punch.max(zoom.amount);
+    }
+
+    let (offset, angle) = shake_offset(effects.trauma * settings.screen_shake, real_time.elapsed_secs());
+    transform.translation = offset.extend(transform.translation.z);
+    transform.rotation = Quat::from_rotation_z(angle);
+    projection.scale = 1.0 - effects.zoom_punch * settings.screen_shake;
+}
+
+/// Camera offset and rotation for `trauma` at time `secs`.
+///
+/// Built from a few sine waves at unrelated frequencies, which wander smoothly without repeating noticeably
+fn shake_offset(trauma: f32, secs: f32) -> (Vec2, f32) {
+    let shake = trauma.clamp(0.0, 1.0).powi(2);
+    let wave = |seed: f32| {
+        let t = secs * SHAKE_FREQUENCY + seed;
+        (t.sin() + (t * 1.73 + 1.1).sin() * 0.5 + (t * 2.91 + 2.3).sin() * 0.25) / 1.75
+    };
+    let offset = Vec2::new(wave(0.0), wave(17.0)) * MAX_SHAKE_OFFSET * shake;
+    (offset, wave(41.0) * MAX_SHAKE_ANGLE * shake)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shake_stays_within_limits_and_stops_without_trauma() {
+        assert_eq!(shake_offset(0.0, 3.7), (Vec2::ZERO, 0.0));
+        for step in 0..200 {
+            let (offset, angle) = shake_offset(1.0, step as f32 * 0.013);
+            assert!(offset.x.abs() <= MAX_SHAKE_OFFSET && offset.y.abs() <= MAX_SHAKE_OFFSET);
+            assert!(angle.abs() <= MAX_SHAKE_ANGLE);
+        }
+        // Small knocks are much gentler than big ones
+        let gentle = (0..200)
+            .map(|step| shake_offset(0.25, step as f32 * 0.013).0.length())
+            .fold(0.0, f32::max);
+        assert!(gentle <= MAX_SHAKE_OFFSET * 0.0625 * 2.0_f32.sqrt());
+    }
 }
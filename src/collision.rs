@@ -0,0 +1,201 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+    constants::WINDOW_DIMENSIONS,
+    game::{GameplaySystem, ResetAppExt},
+    physics::{move_with_velocity, wrap_position, WrappingMovement},
+};
+
+pub struct CollisionPlugin;
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CollisionStarted>();
+        app.add_event::<CollisionEnded>();
+        app.init_resetting_resource::<Contacts>();
+        app.add_systems(FixedUpdate, detect_collisions.in_set(CollisionSystem));
+        app.configure_sets(
+            FixedUpdate,
+            CollisionSystem
+                .in_set(GameplaySystem)
+                .after(move_with_velocity)
+                .after(wrap_position),
+        );
+    }
+}
+
+/// Systems that send `CollisionStarted` and `CollisionEnded`. Consumers should run after this
+#[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct CollisionSystem;
+
+/// Shape used to detect overlap, centered on the entity's translation
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub enum Collider {
+    Circle { radius: f32 },
+    Aabb { half_extents: Vec2 },
+}
+
+impl Collider {
+    pub fn circle(radius: f32) -> Self {
+        Self::Circle { radius }
+    }
+
+    pub fn aabb(size: Vec2) -> Self {
+        Self::Aabb { half_extents: size / 2.0 }
+    }
+
+    /// Half the height of this shape, used to bound it during the broad phase
+    fn half_height(&self) -> f32 {
+        match self {
+            Collider::Circle { radius } => *radius,
+            Collider::Aabb { half_extents } => half_extents.y,
+        }
+    }
+
+    /// Returns true if this collider overlaps `other` when `other` is offset from it by `delta`
+    fn overlaps(&self, other: &Collider, delta: Vec2) -> bool {
+        match (self, other) {
+            (Collider::Circle { radius: a }, Collider::Circle { radius: b }) => delta.length_squared() <= (a + b) * (a + b),
+            (Collider::Aabb { half_extents: a }, Collider::Aabb { half_extents: b }) => {
+                let limit = *a + *b;
+                delta.x.abs() <= limit.x && delta.y.abs() <= limit.y
+            }
+            (Collider::Circle { radius }, Collider::Aabb { half_extents }) => circle_overlaps_aabb(*radius, *half_extents, -delta),
+            (Collider::Aabb { half_extents }, Collider::Circle { radius }) => circle_overlaps_aabb(*radius, *half_extents, delta),
+        }
+    }
+}
+
+/// `offset` is the position of the circle relative to the center of the box
+fn circle_overlaps_aabb(radius: f32, half_extents: Vec2, offset: Vec2) -> bool {
+    let closest = offset.clamp(-half_extents, half_extents);
+    offset.distance_squared(closest) <= radius * radius
+}
+
+/// Sent the first frame two colliders overlap
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionStarted {
+    pub a: Entity,
+    pub b: Entity,
+}
+
+impl CollisionStarted {
+    /// Returns the other entity if `entity` is part of this collision
+    pub fn other(&self, entity: Entity) -> Option<Entity> {
+        if self.a == entity {
+            Some(self.b)
+        } else if self.b == entity {
+            Some(self.a)
+        } else {
+            None
+        }
+    }
+}
+
+/// Sent the first frame two colliders stop overlapping, or when either of them is despawned
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionEnded {
+    pub a: Entity,
+    pub b: Entity,
+}
+
+/// Pairs of entities that were overlapping at the end of the last detection pass. Lower entity first
+#[derive(Resource, Default)]
+struct Contacts(HashSet<(Entity, Entity)>);
+
+/// Horizontal offset from `a` to `b`, taking the shorter way around the wrap seam if either wraps
+pub fn horizontal_delta(a: f32, b: f32, wraps: bool) -> f32 {
+    let delta = b - a;
+    if !wraps {
+        return delta;
+    }
+    const HALF_WIDTH: f32 = WINDOW_DIMENSIONS.x / 2.0;
+    if delta > HALF_WIDTH {
+        delta - WINDOW_DIMENSIONS.x
+    } else if delta < -HALF_WIDTH {
+        delta + WINDOW_DIMENSIONS.x
+    } else {
+        delta
+    }
+}
+
+/// Finds every overlapping pair of colliders and sends events for pairs that started or stopped touching.
+///
+/// The broad phase sorts colliders by their bottom edge and sweeps upwards, so only pairs that overlap
+/// vertically reach the narrow phase. Sweeping on the y axis means the horizontal wrap seam never splits
+/// a pair.
+fn detect_collisions(
+    query: Query<(Entity, &Transform, &Collider, Has<WrappingMovement>)>,
+    mut contacts: ResMut<Contacts>,
+    mut started_writer: EventWriter<CollisionStarted>,
+    mut ended_writer: EventWriter<CollisionEnded>,
+) {
+    let mut bodies: Vec<_> = query
+        .iter()
+        .map(|(entity, transform, collider, wraps)| (entity, transform.translation.truncate(), collider, wraps))
+        .collect();
+    bodies.sort_by(|a, b| (a.1.y - a.2.half_height()).total_cmp(&(b.1.y - b.2.half_height())));
+
+    let mut current = HashSet::new();
+    for (i, (entity_a, position_a, collider_a, wraps_a)) in bodies.iter().enumerate() {
+        let top = position_a.y + collider_a.half_height();
+        for (entity_b, position_b, collider_b, wraps_b) in bodies[i + 1..].iter() {
+            if position_b.y - collider_b.half_height() > top {
+                break;
+            }
+            let delta = Vec2::new(
+                horizontal_delta(position_a.x, position_b.x, *wraps_a || *wraps_b),
+                position_b.y - position_a.y,
+            );
+            if collider_a.overlaps(collider_b, delta) {
+                current.insert(ordered_pair(*entity_a, *entity_b));
+            }
+        }
+    }
+
+    for &(a, b) in current.difference(&contacts.0) {
+        started_writer.send(CollisionStarted { a, b });
+    }
+    for &(a, b) in contacts.0.difference(&current) {
+        ended_writer.send(CollisionEnded { a, b });
+    }
+    contacts.0 = current;
+}
+
+fn ordered_pair(a: Entity, b: Entity) -> (Entity, Entity) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circles_overlap() {
+        let a = Collider::circle(10.0);
+        let b = Collider::circle(5.0);
+        assert!(a.overlaps(&b, Vec2::new(14.0, 0.0)));
+        assert!(!a.overlaps(&b, Vec2::new(12.0, 12.0)));
+    }
+
+    #[test]
+    fn circle_and_aabb_overlap() {
+        let circle = Collider::circle(5.0);
+        let aabb = Collider::aabb(Vec2::new(20.0, 10.0));
+        assert!(circle.overlaps(&aabb, Vec2::new(14.0, 0.0)));
+        assert!(aabb.overlaps(&circle, Vec2::new(-14.0, 0.0)));
+        // Near the corner, outside the radius
+        assert!(!circle.overlaps(&aabb, Vec2::new(14.0, 9.0)));
+    }
+
+    #[test]
+    fn delta_wraps_across_seam() {
+        let right_edge = WINDOW_DIMENSIONS.x / 2.0 - 5.0;
+        let left_edge = -right_edge;
+        assert_eq!(horizontal_delta(right_edge, left_edge, true), 10.0);
+        assert_eq!(horizontal_delta(right_edge, left_edge, false), -2.0 * right_edge);
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+use crate::{
+    input_bindings::{BindingTarget, InputBindings, InputCapture, ResetBindings, StartRebind},
+    main_menu::MenuScreen,
+    ui::{AcceptAction, Focusable, Focused, MenuActionAppExt},
+};
+
+pub struct ControlsMenuPlugin;
+impl Plugin for ControlsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_menu_action::<StartRebind>();
+        app.add_menu_action::<ResetBindings>();
+        app.add_systems(OnEnter(MenuScreen::Controls), spawn_controls_screen);
+        app.add_systems(
+            Update,
+            update_binding_texts
+                .run_if(in_state(MenuScreen::Controls))
+                .run_if(resource_changed::<InputBindings>.or(resource_changed::<InputCapture>)),
+        );
+    }
+}
+
+fn spawn_controls_screen(mut commands: Commands, bindings: Res<InputBindings>, capture: Res<InputCapture>) {
+    commands.spawn(ControlsScreen).with_children(|parent| {
+        parent.spawn(ControlsTitleText);
+        for (index, target) in BindingTarget::ALL.into_iter().enumerate() {
+            let mut row = parent.spawn((
+                ControlsButton,
+                BindingRow(target),
+                Text::new(binding_text(target, &bindings, &capture)),
+                AcceptAction::new(StartRebind(target)),
+            ));
+            if index == 0 {
+                row.insert(Focused);
+            }
+        }
+        parent.spawn((
+            ControlsButton,
+            Text::new("Reset to Defaults"),
+            AcceptAction::<ResetBindings>::default(),
+        ));
+    });
+}
+
+fn binding_text(target: BindingTarget, bindings: &InputBindings, capture: &InputCapture) -> String {
+    if capture.target() == Some(target) {
+        return format!("{}: press an input (Esc to cancel)", target.label());
+    }
+    let bound: Vec<String> = bindings.get(target).iter().map(ToString::to_string).collect();
+    if bound.is_empty() {
+        format!("{}: unbound", target.label())
+    } else {
+        format!("{}: {}", target.label(), bound.join(", "))
+    }
+}
+
+fn update_binding_texts(bindings: Res<InputBindings>, capture: Res<InputCapture>, mut rows: Query<(&BindingRow, &mut Text)>) {
+    for (row, mut text) in rows.iter_mut() {
+        **text = binding_text(row.0, &bindings, &capture);
+    }
+}
+
+/// Shows the inputs bound to an action, and rebinds it when accepted
+#[derive(Component)]
+struct BindingRow(BindingTarget);
+
+#[derive(Component)]
+#[require(Text(|| Text::new("Controls")), TextFont(Self::font))]
+struct ControlsTitleText;
+
+impl ControlsTitleText {
+    fn font() -> TextFont {
+        TextFont {
+            font_size: 48.0,
+            ..default()
+        }
+    }
+}
+
+#[derive(Component, Default)]
+#[require(Focusable, Node(Self::node))]
+struct ControlsButton;
+
+impl ControlsButton {
+    fn node() -> Node {
+        Node {
+            width: Val::Percent(100.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+#[require(Node(Self::node), BackgroundColor(Self::background_color), StateScoped<MenuScreen>(|| StateScoped(MenuScreen::Controls)))]
+struct ControlsScreen;
+
+impl ControlsScreen {
+    fn node() -> Node {
+        Node {
+            width: Val::Percent(50.0),
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            align_self: AlignSelf::Center,
+            justify_self: JustifySelf::Center,
+            row_gap: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(24.0)),
+            ..default()
+        }
+    }
+    fn background_color() -> BackgroundColor {
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.85))
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::horizontal_delta,
+    enemy::Enemy,
+    game::{DespawnOnReset, GameplaySystem, ResetAppExt},
+    pause_menu::PausedState,
+    player::{Player, PlayerScreetched},
+    GameState,
+};
+
+/// Seconds before the bat can screetch again
+const COOLDOWN_SECS: f32 = 2.5;
+/// Seconds an enemy stays visible after the pulse passes over it
+const REVEAL_SECS: f32 = 3.0;
+/// Growth of the pulse radius in logical pixels per second
+const PULSE_SPEED: f32 = 900.0;
+const PULSE_MAX_RADIUS: f32 = 600.0;
+const PULSE_COLOR: Color = Color::srgb(0.6, 0.9, 1.0);
+/// Opacity of camouflaged enemies that have not been revealed
+const CAMOUFLAGE_ALPHA: f32 = 0.15;
+
+pub struct EcholocationPlugin;
+impl Plugin for EcholocationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resetting_resource::<EcholocationCooldown>();
+        app.add_systems(
+            FixedUpdate,
+            (
+                tick_cooldown,
+                emit_pulse.run_if(on_event::<PlayerScreetched>),
+                expand_pulses.after(emit_pulse),
+                tick_revealed.after(expand_pulses),
+            )
+                .in_set(GameplaySystem)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PausedState::Unpaused)),
+        );
+        app.add_systems(Update, (apply_camouflage, draw_pulses));
+    }
+}
+
+/// Time until the bat can screetch again
+#[derive(Resource)]
+pub struct EcholocationCooldown(Timer);
+
+impl Default for EcholocationCooldown {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(COOLDOWN_SECS, TimerMode::Once);
+        // Start out ready
+        timer.tick(timer.duration());
+        Self(timer)
+    }
+}
+
+impl EcholocationCooldown {
+    pub fn ready(&self) -> bool {
+        self.0.finished()
+    }
+
+    /// Starts the cooldown
+    pub fn trigger(&mut self) {
+        self.0.reset();
+    }
+}
+
+/// Enemy that is nearly invisible unless `Revealed`
+#[derive(Component, Default)]
+pub struct Camouflaged;
+
+/// Enemy that was caught by an echolocation pulse and is visible until the timer finishes
+#[derive(Component)]
+pub struct Revealed(Timer);
+
+impl Default for Revealed {
+    fn default() -> Self {
+        Self(Timer::from_seconds(REVEAL_SECS, TimerMode::Once))
+    }
+}
+
+/// Expanding ring centered where the bat screetched
+#[derive(Component, Default)]
+#[require(Transform, DespawnOnReset)]
+struct EcholocationPulse {
+    radius: f32,
+}
+
+fn tick_cooldown(time: Res<Time>, mut cooldown: ResMut<EcholocationCooldown>) {
+    cooldown.0.tick(time.delta());
+}
+
+fn emit_pulse(mut commands: Commands, player: Single<&Transform, With<Player>>) {
+    let origin = player.into_inner().translation;
+    commands.spawn((EcholocationPulse::default(), Transform::from_translation(origin)));
+}
+
+/// Grows every pulse and reveals the enemies inside it
+fn expand_pulses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pulses: Query<(Entity, &mut EcholocationPulse, &Transform)>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+) {
+    for (pulse_entity, mut pulse, pulse_transform) in pulses.iter_mut() {
+        pulse.radius += PULSE_SPEED * time.delta_secs();
+        let origin = pulse_transform.translation.truncate();
+        for (enemy_entity, enemy_transform) in enemies.iter() {
+            let position = enemy_transform.translation.truncate();
+            let delta = Vec2::new(horizontal_delta(origin.x, position.x, true), position.y - origin.y);
+            if delta.length() <= pulse.radius {
+                // Inserting replaces any existing timer, so enemies caught again stay visible longer
+                commands.entity(enemy_entity).try_insert(Revealed::default());
+            }
+        }
+        if pulse.radius >= PULSE_MAX_RADIUS {
+            commands.entity(pulse_entity).despawn();
+        }
+    }
+}
+
+fn tick_revealed(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut Revealed)>) {
+    for (entity, mut revealed) in query.iter_mut() {
+        revealed.0.tick(time.delta());
+        if revealed.0.finished() {
+            commands.entity(entity).remove::<Revealed>();
+        }
+    }
+}
+
+fn apply_camouflage(mut query: Query<(&mut Sprite, Has<Revealed>), With<Camouflaged>>) {
+    for (mut sprite, revealed) in query.iter_mut() {
+        let alpha = if revealed { 1.0 } else { CAMOUFLAGE_ALPHA };
+        sprite.color.set_alpha(alpha);
+    }
+}
+
+/// Draws each pulse as a ring that fades out as it grows
+fn draw_pulses(mut gizmos: Gizmos, pulses: Query<(&EcholocationPulse, &Transform)>) {
+    for (pulse, transform) in pulses.iter() {
+        let fade = 1.0 - pulse.radius / PULSE_MAX_RADIUS;
+        gizmos.circle_2d(
+            Isometry2d::from_translation(transform.translation.truncate()),
+            pulse.radius,
+            PULSE_COLOR.with_alpha(fade),
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+
+use crate::{
+    animation::{AnimationClip, AnimationClips, Animator, Direction, PlaybackMode},
+    audio::Buzzing,
+    collision::{Collider, CollisionStarted, CollisionSystem},
+    constants::{WINDOW_BOTTOM, WINDOW_DIMENSIONS},
+    echolocation::Camouflaged,
+    game::{DespawnOnReset, EnemyEaten, GameRng, GameplaySystem, ResetAppExt},
+    loading::LoadingAssets,
+    pause_menu::PausedState,
+    physics::{move_with_velocity, wrap_position, Velocity, WrappingMovement},
+    placeholder::{PlaceholderColors, PlaceholderFallback},
+    player::Player,
+    settings::Settings,
+    GameState,
+};
+
+pub use species::{MovementPattern, Species, SPECIES};
+
+mod species;
+
+/// Seconds between spawn attempts
+const SPAWN_INTERVAL_SECS: f32 = 1.5;
+const MAX_ENEMIES: usize = 8;
+/// Enemies never spawn closer than this to the top or bottom of the window
+const SPAWN_VERTICAL_MARGIN: f32 = 100.0;
+/// Enemies spawn this far beyond the side of the window, so they can be heard coming before they fly in
+const SPAWN_OFFSCREEN_DISTANCE: f32 = 160.0;
+/// Name of the clip every insect loops while flying
+const FLY_CLIP: &str = "fly";
+
+pub struct EnemyPlugin;
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resetting_resource::<EnemySpawner>();
+        app.add_systems(Startup, load_species_sprites);
+        app.add_systems(
+            FixedUpdate,
+            (
+                spawn_enemies,
+                move_in_pattern.before(move_with_velocity),
+                wrap_once_on_screen.after(move_with_velocity).before(wrap_position),
+                eat_enemies.after(CollisionSystem),
+            )
+                .in_set(GameplaySystem)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PausedState::Unpaused)),
+        );
+    }
+}
+
+/// An insect the bat can eat. Wraps around the window once it has flown in
+#[derive(Component)]
+#[require(Sprite, Velocity, Direction, Buzzing, DespawnOnReset)]
+pub struct Enemy {
+    species: &'static Species,
+    /// 1.0 when flying right, -1.0 when flying left
+    heading: f32,
+    /// Seconds since spawning, used to drive the movement pattern
+    elapsed: f32,
+}
+
+/// Periodically spawns enemies picked from `SPECIES` by spawn weight
+#[derive(Resource)]
+pub struct EnemySpawner {
+    timer: Timer,
+    weights: WeightedIndex<u32>,
+}
+
+impl Default for EnemySpawner {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(SPAWN_INTERVAL_SECS, TimerMode::Repeating),
+            weights: WeightedIndex::new(SPECIES.iter().map(|species| species.spawn_weight))
+                .expect("Species table should have at least one spawnable species"),
+        }
+    }
+}
+
+/// Image and atlas layout of every species' strip, in the same order as `SPECIES`
+#[derive(Resource)]
+struct SpeciesSprites(Vec<(Handle<Image>, Handle<TextureAtlasLayout>)>);
+
+/// Loads every strip up front, so insects don't appear blank while theirs loads. Missing strips aren't fatal
+fn load_species_sprites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut loading: ResMut<LoadingAssets>,
+) {
+    let sprites = SPECIES
+        .iter()
+        .map(|species| {
+            let image = asset_server.load(species.sprite.path);
+            loading.track_optional(&image);
+            let layout = TextureAtlasLayout::from_grid(species.sprite.size, species.sprite.frames, 1, None, None);
+            (image, texture_atlas_layouts.add(layout))
+        })
+        .collect();
+    commands.insert_resource(SpeciesSprites(sprites));
+}
+
+impl MovementPattern {
+    /// Velocity of an enemy following this pattern `elapsed` seconds after spawning, before heading is applied
+    fn velocity(&self, speed: f32, elapsed: f32) -> Vec2 {
+        match *self {
+            MovementPattern::Straight => Vec2::new(speed, 0.0),
+            MovementPattern::Sine { amplitude, frequency } => {
+                let angular = TAU * frequency;
+                Vec2::new(speed, amplitude * angular * (angular * elapsed).cos())
+            }
+            MovementPattern::Zigzag { amplitude, period } => {
+                let vertical = 4.0 * amplitude / period;
+                if elapsed % period < period / 2.0 {
+                    Vec2::new(speed, vertical)
+                } else {
+                    Vec2::new(speed, -vertical)
+                }
+            }
+            MovementPattern::Dart { hover_secs, dash_secs } => {
+                let cycle = hover_secs + dash_secs;
+                if elapsed % cycle < hover_secs {
+                    Vec2::ZERO
+                } else {
+                    // Dash fast enough that the average speed still matches the species speed
+                    Vec2::new(speed * cycle / dash_secs, 0.0)
+                }
+            }
+        }
+    }
+}
+
+fn spawn_enemies(
+    mut commands: Commands,
+    mut spawner: ResMut<EnemySpawner>,
+    mut rng: ResMut<GameRng>,
+    time: Res<Time>,
+    enemies: Query<(), With<Enemy>>,
+    species_sprites: Res<SpeciesSprites>,
+    settings: Res<Settings>,
+) {
+    spawner.timer.tick(time.delta());
+    if !spawner.timer.just_finished() || enemies.iter().len() >= MAX_ENEMIES {
+        return;
+    }
+    let index = spawner.weights.sample(&mut **rng);
+    let species = &SPECIES[index];
+
+    // Enter from whichever side was picked, flying towards the other
+    let heading = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+    let x = -heading * (WINDOW_DIMENSIONS.x / 2.0 + SPAWN_OFFSCREEN_DISTANCE);
+    let y = rng.gen_range(WINDOW_BOTTOM + SPAWN_VERTICAL_MARGIN..-WINDOW_BOTTOM - SPAWN_VERTICAL_MARGIN);
+    let direction = if heading < 0.0 { Direction::Backward } else { Direction::Forward };
+
+    let (image, layout) = &species_sprites.0[index];
+    let sprite = Sprite {
+        color: species.sprite.palette_color(settings.colorblind_palette),
+        ..Sprite::from_atlas_image(
+            image.clone(),
+            TextureAtlas {
+                layout: layout.clone(),
+                index: 0,
+            },
+        )
+    };
+    let fly_clip = AnimationClip::new(
+        0..=species.sprite.frames as usize - 1,
+        species.sprite.frames as f32 / species.sprite.animation_secs,
+        PlaybackMode::Loop,
+    )
+    .expect("species animation_secs should be positive");
+
+    debug!("Spawning {} at ({x}, {y})", species.name);
+    let mut entity = commands.spawn((
+        Enemy {
+            species,
+            heading,
+            elapsed: 0.0,
+        },
+        sprite,
+        Collider::aabb(species.sprite.size.as_vec2()),
+        direction,
+        Animator::new(AnimationClips::default().with(FLY_CLIP, fly_clip), FLY_CLIP),
+        PlaceholderFallback::new(species.sprite.size, UVec2::new(species.sprite.frames, 1)).with_colors(PlaceholderColors::TINTABLE),
+        Transform::from_xyz(x, y, 0.0),
+    ));
+    if species.camouflaged {
+        entity.insert(Camouflaged);
+    }
+}
+
+/// Steers every enemy according to its species movement pattern
+fn move_in_pattern(time: Res<Time>, mut query: Query<(&mut Enemy, &mut Velocity)>) {
+    for (mut enemy, mut velocity) in query.iter_mut() {
+        enemy.elapsed += time.delta_secs();
+        let species = enemy.species;
+        let pattern_velocity = species.pattern.velocity(species.speed, enemy.elapsed);
+        **velocity = Vec2::new(pattern_velocity.x * enemy.heading, pattern_velocity.y);
+    }
+}
+
+/// Starts wrapping enemies around the window once they have flown onto it
+fn wrap_once_on_screen(mut commands: Commands, enemies: Query<(Entity, &Transform, Has<WrappingMovement>), With<Enemy>>) {
+    for (entity, transform, wrapping) in enemies.iter() {
+        if !wrapping && transform.translation.x.abs() <= WINDOW_DIMENSIONS.x / 2.0 {
+            commands.entity(entity).insert(WrappingMovement);
+        }
+    }
+}
+
+/// Despawns enemies the player touches
+fn eat_enemies(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionStarted>,
+    mut writer: EventWriter<EnemyEaten>,
+    player: Single<Entity, With<Player>>,
+    enemies: Query<(&Enemy, &Transform)>,
+) {
+    let player = player.into_inner();
+    for collision in collisions.read() {
+        let Some(other) = collision.other(player) else {
+            continue;
+        };
+        if let Ok((enemy, transform)) = enemies.get(other) {
+            commands.entity(other).despawn_recursive();
+            writer.send(EnemyEaten {
+                species: enemy.species,
+                position: transform.translation.truncate(),
+            });
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Data table describing every kind of insect the bat can hunt.
+//!
+//! Adding a new bug only requires adding an entry to `SPECIES`; the spawning and movement systems read
+//! everything they need from here.
+use bevy::prelude::*;
+
+/// How a species moves vertically (and sometimes horizontally) while it crosses the screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MovementPattern {
+    /// Flies in a straight horizontal line
+    Straight,
+    /// Bobs up and down smoothly
+    Sine { amplitude: f32, frequency: f32 },
+    /// Alternates between rising and falling at a constant rate
+    Zigzag { amplitude: f32, period: f32 },
+    /// Hovers in place, then darts forward
+    Dart { hover_secs: f32, dash_secs: f32 },
+}
+
+/// Sprite strip used to draw a species, tinted with its palette color.
+///
+/// The strips aren't distributed with the source, so a placeholder is drawn in their place when missing
+#[derive(Clone, Copy, Debug)]
+pub struct SpeciesSprite {
+    /// Asset path of a strip of `frames` cells of `size`, laid out left to right
+    pub path: &'static str,
+    pub size: UVec2,
+    pub frames: u32,
+    /// Seconds to play the whole strip once
+    pub animation_secs: f32,
+    pub color: Color,
+    /// Used instead of `color` with the colorblind palette. Taken from the Okabe-Ito palette
+    pub colorblind_color: Color,
+}
+
+impl SpeciesSprite {
+    /// Color this species is drawn in, given whether the colorblind palette is on
+    pub fn palette_color(&self, colorblind_palette: bool) -> Color {
+        if colorblind_palette {
+            self.colorblind_color
+        } else {
+            self.color
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Species {
+    pub name: &'static str,
+    pub sprite: SpeciesSprite,
+    /// Horizontal speed in logical pixels per second
+    pub speed: f32,
+    pub pattern: MovementPattern,
+    /// Points awarded for eating one
+    pub points: u32,
+    /// Relative likelihood of this species being picked when spawning
+    pub spawn_weight: u32,
+    /// Nearly invisible until revealed by echolocation
+    pub camouflaged: bool,
+    /// Seconds the world's darkening is wound back when one is eaten
+    pub light_restored_secs: f32,
+}
+
+pub const SPECIES: &[Species] = &[
+    Species {
+        name: "Firefly",
+        sprite: SpeciesSprite {
+            path: "sprites/insects/firefly_strip.png",
+            size: UVec2::splat(12),
+            frames: 4,
+            animation_secs: 0.3,
+            color: Color::srgb(1.0, 0.9, 0.3),
+            colorblind_color: Color::srgb(0.94, 0.89, 0.26),
+        },
+        speed: 120.0,
+        pattern: MovementPattern::Sine {
+            amplitude: 40.0,
+            frequency: 0.5,
+        },
+        points: 10,
+        spawn_weight: 6,
+        camouflaged: false,
+        light_restored_secs: 15.0,
+    },
+    Species {
+        name: "Moth",
+        sprite: SpeciesSprite {
+            path: "sprites/insects/moth_strip.png",
+            size: UVec2::new(20, 16),
+            frames: 4,
+            animation_secs: 0.4,
+            color: Color::srgb(0.75, 0.7, 0.6),
+            colorblind_color: Color::srgb(0.9, 0.62, 0.0),
+        },
+        speed: 90.0,
+        pattern: MovementPattern::Zigzag {
+            amplitude: 50.0,
+            period: 1.5,
+        },
+        points: 15,
+        spawn_weight: 5,
+        camouflaged: false,
+        light_restored_secs: 0.0,
+    },
+    Species {
+        name: "Gnat",
+        sprite: SpeciesSprite {
+            path: "sprites/insects/gnat_strip.png",
+            size: UVec2::splat(6),
+            frames: 2,
+            animation_secs: 0.1,
+            color: Color::srgb(0.2, 0.2, 0.2),
+            colorblind_color: Color::srgb(0.0, 0.45, 0.7),
+        },
+        speed: 200.0,
+        pattern: MovementPattern::Zigzag {
+            amplitude: 15.0,
+            period: 0.4,
+        },
+        points: 5,
+        spawn_weight: 8,
+        camouflaged: true,
+        light_restored_secs: 0.0,
+    },
+    Species {
+        name: "Mosquito",
+        sprite: SpeciesSprite {
+            path: "sprites/insects/mosquito_strip.png",
+            size: UVec2::new(10, 8),
+            frames: 2,
+            animation_secs: 0.1,
+            color: Color::srgb(0.5, 0.3, 0.3),
+            colorblind_color: Color::srgb(0.84, 0.37, 0.0),
+        },
+        speed: 160.0,
+        pattern: MovementPattern::Dart {
+            hover_secs: 0.8,
+            dash_secs: 0.4,
+        },
+        points: 20,
+        spawn_weight: 4,
+        camouflaged: true,
+        light_restored_secs: 0.0,
+    },
+    Species {
+        name: "Dragonfly",
+        sprite: SpeciesSprite {
+            path: "sprites/insects/dragonfly_strip.png",
+            size: UVec2::new(32, 12),
+            frames: 4,
+            animation_secs: 0.2,
+            color: Color::srgb(0.2, 0.6, 0.8),
+            colorblind_color: Color::srgb(0.34, 0.71, 0.91),
+        },
+        speed: 320.0,
+        pattern: MovementPattern::Dart {
+            hover_secs: 1.2,
+            dash_secs: 0.3,
+        },
+        points: 40,
+        spawn_weight: 2,
+        camouflaged: false,
+        light_restored_secs: 0.0,
+    },
+    Species {
+        name: "Cicada",
+        sprite: SpeciesSprite {
+            path: "sprites/insects/cicada_strip.png",
+            size: UVec2::new(24, 18),
+            frames: 4,
+            animation_secs: 0.3,
+            color: Color::srgb(0.35, 0.5, 0.25),
+            colorblind_color: Color::srgb(0.0, 0.62, 0.45),
+        },
+        speed: 70.0,
+        pattern: MovementPattern::Straight,
+        points: 25,
+        spawn_weight: 3,
+        camouflaged: false,
+        light_restored_secs: 0.0,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn species_table_valid() {
+        let mut names = HashSet::new();
+        let mut colorblind_colors = Vec::new();
+        for species in SPECIES {
+            assert!(names.insert(species.name), "duplicate species {}", species.name);
+            assert!(
+                !colorblind_colors.contains(&species.sprite.colorblind_color),
+                "{} shares a colorblind color",
+                species.name
+            );
+            colorblind_colors.push(species.sprite.colorblind_color);
+            assert!(species.spawn_weight > 0, "{} can never spawn", species.name);
+            assert!(species.sprite.frames > 0, "{} has no frames", species.name);
+            assert!(species.sprite.animation_secs > 0.0, "{} never animates", species.name);
+        }
+    }
+}
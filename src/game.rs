@@ -1,24 +1,87 @@
 use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
 
 use crate::{
-    animation::{animate_sprites, direct_sprites},
-    audio::SoundEffectSystem,
+    animation::{animate_sprites, direct_sprites, AnimationFinished, AnimationSystem},
+    enemy::Species,
     input_translation::{GameInput, InputTranslationSystem},
     pause_menu::PausedState,
     physics::{add_friction, add_gravity, move_with_velocity, wrap_position},
-    player::{check_player_crashed, handle_input, reset_player, spawn_player, PlayerFlapped, PlayerScreetched},
+    player::{animate_player, check_player_crashed, handle_input, reset_player, spawn_player, PlayerFlapped, PlayerScreetched},
     GameState,
 };
 
-#[derive(Event, Default, Clone)]
-pub struct Reset;
+/// Restores the world to how it was at the start of a run, then moves to `next_state`
+#[derive(Event, Clone)]
+pub struct Reset {
+    next_state: GameState,
+}
+
+impl Default for Reset {
+    fn default() -> Self {
+        Self::then(GameState::Ready)
+    }
+}
+
+impl Reset {
+    /// Resets, then moves to `next_state` instead of `GameState::Ready`
+    pub fn then(next_state: GameState) -> Self {
+        Self { next_state }
+    }
+}
+
+/// Entities that only exist during a run and are despawned by `Reset`
+#[derive(Component, Default)]
+pub struct DespawnOnReset;
+
+/// Random numbers for gameplay. Reseeded on every reset, so a run plays out the same given its seed and inputs
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng {
+    seed: u64,
+    #[deref]
+    rng: StdRng,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::with_seed(rand::random())
+    }
+}
 
+impl GameRng {
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+/// Sent when the player eats an enemy
 #[derive(Event)]
-pub struct EnemyEaten;
+pub struct EnemyEaten {
+    pub species: &'static Species,
+    /// Where the enemy was when it was eaten
+    pub position: Vec2,
+}
 
+/// Simulation rate, independent of the frame rate so movement is the same on every machine
+pub const TICKS_PER_SECOND: f64 = 60.0;
+
+/// Systems that simulate the game. These run in `FixedUpdate`, so `Time` is always one tick long
 #[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
 pub struct GameplaySystem;
 
+/// Systems that restore state on frames where `Reset` was sent.
+///
+/// Everything in this set is finished before the game leaves its current state
+#[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct ResetSystem;
+
 /// Systems that send the Gameover Event
 #[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
 pub struct GameoverTriggersSubSystem;
@@ -30,17 +93,17 @@ impl Plugin for GamePlugin {
         app.add_event::<PlayerFlapped>();
         app.add_event::<PlayerScreetched>();
         app.add_event::<EnemyEaten>();
+        app.init_resetting_resource::<GameRng>();
         app.add_systems(
             PreUpdate,
             check_game_start.after(InputTranslationSystem).run_if(in_state(GameState::Ready)),
         );
         app.add_systems(Startup, spawn_player);
+        app.insert_resource(Time::<Fixed>::from_hz(TICKS_PER_SECOND));
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
                 handle_input.before(move_with_velocity),
-                animate_sprites.after(handle_input),
-                direct_sprites.after(handle_input),
                 add_gravity.before(move_with_velocity),
                 move_with_velocity,
                 add_friction.after(move_with_velocity),
@@ -51,8 +114,49 @@ impl Plugin for GamePlugin {
                 .run_if(in_state(GameState::Playing))
                 .run_if(in_state(PausedState::Unpaused)),
         );
-        app.add_systems(Update, reset_player);
-        app.configure_sets(Update, GameplaySystem.before(SoundEffectSystem));
+        // Only what is drawn changes every frame, the simulation itself runs in fixed ticks
+        app.add_event::<AnimationFinished>();
+        app.add_systems(
+            Update,
+            (
+                // The bat keeps its idle clip before a run and its death clip after one
+                animate_player
+                    .before(AnimationSystem)
+                    .run_if(in_state(GameState::Playing).or(in_state(GameState::Gameover))),
+                (animate_sprites, direct_sprites).in_set(AnimationSystem).run_if(
+                    in_state(GameState::Ready)
+                        .or(in_state(GameState::Playing))
+                        .or(in_state(GameState::Gameover)),
+                ),
+            )
+                .run_if(in_state(PausedState::Unpaused)),
+        );
+        app.add_systems(Update, (reset_player, despawn_on_reset).in_set(ResetSystem));
+        app.add_systems(Update, reset_game.after(ResetSystem).run_if(on_event::<Reset>));
+        app.configure_sets(Update, ResetSystem.run_if(on_event::<Reset>));
+    }
+}
+
+pub trait ResetAppExt {
+    /// Initializes a resource and restores it to its initial value whenever the game is reset
+    fn init_resetting_resource<R: Resource + FromWorld>(&mut self) -> &mut Self;
+}
+
+impl ResetAppExt for App {
+    fn init_resetting_resource<R: Resource + FromWorld>(&mut self) -> &mut Self {
+        self.init_resource::<R>();
+        self.add_systems(Update, reset_resource::<R>.in_set(ResetSystem))
+    }
+}
+
+fn reset_resource<R: Resource + FromWorld>(world: &mut World) {
+    let resource = R::from_world(world);
+    world.insert_resource(resource);
+}
+
+fn despawn_on_reset(mut commands: Commands, query: Query<Entity, With<DespawnOnReset>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
@@ -65,6 +169,65 @@ fn check_game_start(mut input_reader: EventReader<GameInput>, mut next_state: Re
     }
 }
 
-fn reset_game() {
-    //TODO Reset score, despawn enemies, etc.
+/// Moves to the state requested by `Reset` once everything in `ResetSystem` has finished
+fn reset_game(
+    mut reader: EventReader<Reset>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_paused_state: ResMut<NextState<PausedState>>,
+) {
+    if let Some(reset) = reader.read().last() {
+        next_game_state.set(reset.next_state);
+    }
+    next_paused_state.set(PausedState::Unpaused);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{enemy::Enemy, test_harness::TestGame};
+
+    fn enemy_positions(game: &mut TestGame) -> Vec<Vec3> {
+        let world = game.world_mut();
+        world
+            .query_filtered::<&Transform, With<Enemy>>()
+            .iter(world)
+            .map(|transform| transform.translation)
+            .collect()
+    }
+
+    /// Replays rely on a run playing out identically given its seed and inputs
+    #[test]
+    fn same_seed_and_inputs_play_out_the_same() {
+        let mut games = [TestGame::new(), TestGame::new()];
+        for game in games.iter_mut() {
+            game.world_mut().insert_resource(GameRng::with_seed(7));
+            for tick in 0..240 {
+                if tick % 20 == 0 {
+                    game.send_input(GameInput::Flap);
+                }
+                game.hold_direction(if tick < 120 { 0.5 } else { -1.0 });
+                game.advance_ticks(1);
+            }
+        }
+        let [first, second] = &mut games;
+        assert_eq!(first.player_translation(), second.player_translation());
+        let positions = enemy_positions(first);
+        assert!(!positions.is_empty());
+        assert_eq!(positions, enemy_positions(second));
+    }
+
+    #[test]
+    fn reset_returns_player_to_start() {
+        let mut game = TestGame::new();
+        game.send_input(GameInput::Flap);
+        game.advance_ticks(10);
+        assert_ne!(game.player_translation(), Vec3::ZERO);
+
+        game.world_mut().send_event(Reset::default());
+        // The state requested by the reset is entered on the following frame
+        game.advance_ticks(2);
+        assert_eq!(game.game_state(), GameState::Ready);
+        assert_eq!(game.player_translation(), Vec3::ZERO);
+        assert_eq!(game.player_velocity(), Vec2::ZERO);
+    }
 }
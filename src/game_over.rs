@@ -1,9 +1,9 @@
 use bevy::prelude::*;
 
 use crate::{
-    game::{GameoverTriggersSubSystem, Reset},
-    input_translation::InputTranslationSystem,
-    ui::{accept_action, AcceptAction, Focused},
+    game::Reset,
+    high_scores::PendingHighScore,
+    ui::{AcceptAction, BackAction, Focusable, Focused, MenuActionAppExt},
     GameState,
 };
 
@@ -12,19 +12,40 @@ pub struct GameOverPlugin;
 impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<GameOver>();
+        app.add_event::<EnterInitials>();
+        app.add_sub_state::<GameOverScreen>();
+        app.enable_state_scoped_entities::<GameOverScreen>();
         app.add_systems(Startup, spawn_game_over_menu);
-        app.add_systems(PreUpdate, accept_action::<Reset>.after(InputTranslationSystem));
-        app.add_systems(Update, (set_game_over_reason, set_game_over_state).in_set(GameoverResponseSystem));
-        app.add_systems(OnExit(GameState::Gameover), enter_game_over);
-        app.add_systems(OnExit(GameState::Gameover), exit_game_over);
-        app.configure_sets(Update, GameoverResponseSystem.after(GameoverTriggersSubSystem));
+        app.add_menu_action::<Reset>();
+        app.add_menu_action::<AppExit>();
+        app.add_menu_action::<EnterInitials>();
+        // `GameOver` is sent from `FixedUpdate`, which has already run by the time these read it in `Update`
+        app.add_systems(Update, (set_game_over_reason, set_game_over_state));
+        app.add_systems(Update, enter_initials.run_if(on_event::<EnterInitials>));
+        app.add_systems(OnEnter(GameOverScreen::Menu), enter_game_over);
+        app.add_systems(OnExit(GameOverScreen::Menu), exit_game_over);
     }
 }
 
-#[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
-pub struct GameoverResponseSystem;
+/// Screen shown while in `GameState::Gameover`
+#[derive(SubStates, Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+#[source(GameState = GameState::Gameover)]
+pub enum GameOverScreen {
+    #[default]
+    Menu,
+    /// Entering initials for a new high score
+    EnterInitials,
+}
+
+/// Opens the initials entry for a new high score
+#[derive(Event, Clone, Default)]
+pub struct EnterInitials;
+
+fn enter_initials(mut next_state: ResMut<NextState<GameOverScreen>>) {
+    next_state.set(GameOverScreen::EnterInitials);
+}
 
-#[derive(Event)]
+#[derive(Event, Clone, PartialEq, Debug)]
 pub struct GameOver {
     reason: GameOverReason,
 }
@@ -32,6 +53,10 @@ impl GameOver {
     pub fn new(reason: GameOverReason) -> Self {
         Self { reason }
     }
+
+    pub fn reason(&self) -> &GameOverReason {
+        &self.reason
+    }
 }
 #[derive(Eq, PartialEq, Hash, Debug, Clone)]
 pub enum GameOverReason {
@@ -63,13 +88,26 @@ fn set_game_over_reason(mut events: EventReader<GameOver>, reason_text: Single<&
 
 fn enter_game_over(
     mut commands: Commands,
-    menu_visible: Single<&mut Visibility, With<GameOverMenu>>,
+    menu_visible: Single<&mut Visibility, (With<GameOverMenu>, Without<GameOverHighScoreButton>)>,
     restart_button: Single<Entity, With<GameOverRestartButton>>,
+    high_score_button: Single<(Entity, &mut Node, &mut Visibility), With<GameOverHighScoreButton>>,
+    pending_high_score: Option<Res<PendingHighScore>>,
 ) {
     let mut visible = menu_visible.into_inner();
     *visible = Visibility::Visible;
-    let entity = restart_button.into_inner();
-    commands.entity(entity).insert(Focused);
+
+    // Only offered until the record has been entered
+    let (high_score_entity, mut node, mut high_score_visible) = high_score_button.into_inner();
+    let focused = if pending_high_score.is_some() {
+        node.display = Display::Flex;
+        *high_score_visible = Visibility::Inherited;
+        high_score_entity
+    } else {
+        node.display = Display::None;
+        *high_score_visible = Visibility::Hidden;
+        restart_button.into_inner()
+    };
+    commands.entity(focused).insert(Focused);
 }
 
 fn exit_game_over(
@@ -89,14 +127,41 @@ pub fn spawn_game_over_menu(mut commands: Commands) {
     commands.spawn(GameOverMenu).with_children(|parent| {
         parent.spawn(GameOverTitleText);
         parent.spawn(GameOverReasonText);
+        parent.spawn(GameOverHighScoreButton);
         parent.spawn(GameOverRestartButton);
+        parent.spawn((GameOverMainMenuButton, AcceptAction::new(Reset::then(GameState::MainMenu))));
+        parent.spawn(GameOverQuitButton);
     });
 }
 
 #[derive(Component)]
-#[require(AcceptAction<Reset>)]
+#[require(AcceptAction<Reset>, Focusable, Text(|| Text::new("Restart")))]
 struct GameOverRestartButton;
 
+/// Shown when the run made the high score table
+#[derive(Component)]
+#[require(AcceptAction<EnterInitials>, Focusable, Text(|| Text::new("New High Score! Enter Initials")), Node(Self::node), Visibility(|| Visibility::Hidden))]
+struct GameOverHighScoreButton;
+
+impl GameOverHighScoreButton {
+    fn node() -> Node {
+        Node {
+            grid_column: GridPlacement::span(2),
+            display: Display::None,
+            ..default()
+        }
+    }
+}
+
+/// Requires an `AcceptAction<Reset>` that returns to the main menu
+#[derive(Component)]
+#[require(Focusable, Text(|| Text::new("Main Menu")))]
+struct GameOverMainMenuButton;
+
+#[derive(Component)]
+#[require(AcceptAction<AppExit>, Focusable, Text(|| Text::new("Quit")))]
+struct GameOverQuitButton;
+
 #[derive(Component)]
 #[require(Text, Node(Self::node))]
 struct GameOverReasonText;
@@ -126,7 +191,7 @@ impl GameOverTitleText {
 }
 
 #[derive(Component)]
-#[require(Node(Self::node), BackgroundColor(Self::background_color), BorderColor(Self::border_color), Visibility(|| Visibility::Hidden))]
+#[require(Node(Self::node), BackgroundColor(Self::background_color), BorderColor(Self::border_color), Visibility(|| Visibility::Hidden), BackAction<Reset>)]
 struct GameOverMenu;
 
 impl GameOverMenu {
@@ -142,7 +207,13 @@ impl GameOverMenu {
             justify_items: JustifyItems::Center,
             align_self: AlignSelf::Center,
             justify_self: JustifySelf::Center,
-            grid_template_rows: vec![GridTrack::auto(), GridTrack::auto(), GridTrack::flex(1.0)],
+            grid_template_rows: vec![
+                GridTrack::auto(),
+                GridTrack::auto(),
+                GridTrack::auto(),
+                GridTrack::flex(1.0),
+                GridTrack::flex(1.0),
+            ],
             grid_template_columns: vec![GridTrack::flex(0.5), GridTrack::flex(0.5)],
             ..default()
         }
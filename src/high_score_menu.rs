@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_over::GameOverScreen,
+    high_scores::{HighScoreEntry, HighScores, PendingHighScore},
+    input_translation::{InputTranslationSystem, MenuInput},
+    main_menu::MenuScreen,
+};
+
+const INITIALS_LENGTH: usize = 3;
+/// Characters each initial cycles through. The trailing space allows fewer than three initials
+const INITIALS_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 ";
+const SELECTED_INITIAL_COLOR: Color = Color::srgb(0.95, 0.75, 0.35);
+
+pub struct HighScoreMenuPlugin;
+impl Plugin for HighScoreMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameOverScreen::EnterInitials), spawn_initials_screen);
+        app.add_systems(
+            PreUpdate,
+            edit_initials
+                .after(InputTranslationSystem)
+                .run_if(in_state(GameOverScreen::EnterInitials)),
+        );
+        app.add_systems(Update, update_initials_text.run_if(in_state(GameOverScreen::EnterInitials)));
+        app.add_systems(OnEnter(MenuScreen::HighScores), spawn_high_scores_screen);
+    }
+}
+
+/// Initials being entered, as indices into `INITIALS_ALPHABET`
+#[derive(Component, Default)]
+struct InitialsEntry {
+    letters: [usize; INITIALS_LENGTH],
+    selected: usize,
+}
+
+impl InitialsEntry {
+    fn initials(&self) -> String {
+        self.letters.iter().map(|&letter| INITIALS_ALPHABET[letter] as char).collect()
+    }
+
+    fn cycle_selected(&mut self, step: isize) {
+        let letter = &mut self.letters[self.selected];
+        *letter = (*letter as isize + step).rem_euclid(INITIALS_ALPHABET.len() as isize) as usize;
+    }
+}
+
+/// Up and down change the selected initial, left and right select another, accept saves and back cancels
+fn edit_initials(
+    mut commands: Commands,
+    mut events: EventReader<MenuInput>,
+    entry: Single<&mut InitialsEntry>,
+    pending: Option<Res<PendingHighScore>>,
+    mut high_scores: ResMut<HighScores>,
+    mut next_state: ResMut<NextState<GameOverScreen>>,
+) {
+    let mut entry = entry.into_inner();
+    // Skip the accept that opened this screen
+    if entry.is_added() {
+        events.clear();
+        return;
+    }
+    for event in events.read() {
+        match event {
+            MenuInput::Up => entry.cycle_selected(1),
+            MenuInput::Down => entry.cycle_selected(-1),
+            MenuInput::Left => entry.selected = entry.selected.saturating_sub(1),
+            MenuInput::Right => entry.selected = (entry.selected + 1).min(INITIALS_LENGTH - 1),
+            MenuInput::Accept => {
+                if let Some(pending) = pending.as_ref() {
+                    pending.submit(&entry.initials(), &mut high_scores);
+                    commands.remove_resource::<PendingHighScore>();
+                }
+                next_state.set(GameOverScreen::Menu);
+                return;
+            }
+            MenuInput::Back => {
+                next_state.set(GameOverScreen::Menu);
+                return;
+            }
+        }
+    }
+}
+
+fn update_initials_text(
+    entry: Single<&InitialsEntry, Changed<InitialsEntry>>,
+    mut slots: Query<(&InitialSlot, &mut Text, &mut TextColor)>,
+) {
+    let entry = entry.into_inner();
+    for (slot, mut text, mut color) in slots.iter_mut() {
+        let letter = INITIALS_ALPHABET[entry.letters[slot.0]] as char;
+        // Show a blank initial as an underscore so it can still be seen
+        **text = if letter == ' ' { "_".to_owned() } else { letter.to_string() };
+        color.0 = if slot.0 == entry.selected {
+            SELECTED_INITIAL_COLOR
+        } else {
+            Color::WHITE
+        };
+    }
+}
+
+fn spawn_initials_screen(mut commands: Commands, pending: Option<Res<PendingHighScore>>) {
+    let points = pending.map_or(0, |pending| pending.points());
+    commands.spawn((InitialsScreen, InitialsEntry::default())).with_children(|parent| {
+        parent.spawn((Text::new("New High Score!"), heading_font()));
+        parent.spawn(Text::new(format!("{points} points")));
+        parent.spawn(InitialSlots).with_children(|parent| {
+            for slot in 0..INITIALS_LENGTH {
+                parent.spawn(InitialSlot(slot));
+            }
+        });
+        parent.spawn(Text::new("Up/Down: change letter   Left/Right: move   Accept: save   Back: cancel"));
+    });
+}
+
+/// One letter of the initials being entered
+#[derive(Component)]
+#[require(Text, TextColor, TextFont(Self::font))]
+struct InitialSlot(usize);
+
+impl InitialSlot {
+    fn font() -> TextFont {
+        TextFont {
+            font_size: 64.0,
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+#[require(Node(Self::node))]
+struct InitialSlots;
+
+impl InitialSlots {
+    fn node() -> Node {
+        Node {
+            column_gap: Val::Px(24.0),
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+#[require(HighScoreMenuScreen, StateScoped<GameOverScreen>(|| StateScoped(GameOverScreen::EnterInitials)))]
+struct InitialsScreen;
+
+fn spawn_high_scores_screen(mut commands: Commands, high_scores: Res<HighScores>) {
+    commands.spawn(HighScoresScreen).with_children(|parent| {
+        parent.spawn((Text::new("High Scores"), heading_font()));
+        if high_scores.entries().is_empty() {
+            parent.spawn(Text::new("No high scores yet"));
+            return;
+        }
+        spawn_high_score_row(parent, ["", "Name", "Score", "Time", "Date", "Eaten"].map(str::to_owned));
+        for (rank, entry) in high_scores.entries().iter().enumerate() {
+            spawn_high_score_row(parent, high_score_columns(rank + 1, entry));
+        }
+    });
+}
+
+fn high_score_columns(rank: usize, entry: &HighScoreEntry) -> [String; 6] {
+    let eaten = entry
+        .eaten
+        .iter()
+        .map(|(species, count)| format!("{count} {species}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    [
+        format!("{rank}."),
+        entry.initials.clone(),
+        entry.points.to_string(),
+        entry.survived(),
+        entry.date(),
+        eaten,
+    ]
+}
+
+fn spawn_high_score_row(parent: &mut ChildBuilder, columns: [String; 6]) {
+    const COLUMN_WIDTHS: [f32; 6] = [40.0, 80.0, 80.0, 70.0, 120.0, 360.0];
+    parent.spawn(Node::default()).with_children(|parent| {
+        for (text, width) in columns.into_iter().zip(COLUMN_WIDTHS) {
+            parent.spawn((
+                Text::new(text),
+                row_font(),
+                Node {
+                    width: Val::Px(width),
+                    ..default()
+                },
+            ));
+        }
+    });
+}
+
+#[derive(Component)]
+#[require(HighScoreMenuScreen, StateScoped<MenuScreen>(|| StateScoped(MenuScreen::HighScores)))]
+struct HighScoresScreen;
+
+fn heading_font() -> TextFont {
+    TextFont {
+        font_size: 48.0,
+        ..default()
+    }
+}
+
+fn row_font() -> TextFont {
+    TextFont {
+        font_size: 18.0,
+        ..default()
+    }
+}
+
+/// Panel shared by both high score screens
+#[derive(Component, Default)]
+#[require(Node(Self::node), BackgroundColor(Self::background_color))]
+struct HighScoreMenuScreen;
+
+impl HighScoreMenuScreen {
+    fn node() -> Node {
+        Node {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            align_self: AlignSelf::Center,
+            justify_self: JustifySelf::Center,
+            row_gap: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(24.0)),
+            ..default()
+        }
+    }
+    fn background_color() -> BackgroundColor {
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.85))
+    }
+}
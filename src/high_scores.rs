@@ -0,0 +1,208 @@
+//! Best runs, kept in the platform data directory
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{EnemyEaten, GameplaySystem, ResetAppExt},
+    pause_menu::PausedState,
+    persistence,
+    score::Score,
+    GameState,
+};
+
+const HIGH_SCORES_FILE: &str = "high_scores.ron";
+const MAX_HIGH_SCORES: usize = 10;
+const SECS_PER_DAY: u64 = 60 * 60 * 24;
+
+pub struct HighScoresPlugin;
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(persistence::load_or_default::<HighScores>(persistence::data_file(HIGH_SCORES_FILE)));
+        app.init_resetting_resource::<RunStats>();
+        app.add_systems(
+            FixedUpdate,
+            (track_survival_time, count_eaten.run_if(on_event::<EnemyEaten>))
+                .in_set(GameplaySystem)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PausedState::Unpaused)),
+        );
+        app.add_systems(OnEnter(GameState::Gameover), check_for_record);
+        app.add_systems(OnExit(GameState::Gameover), discard_pending_record);
+        app.add_systems(
+            PostUpdate,
+            save_high_scores.run_if(resource_changed::<HighScores>.and(not(resource_added::<HighScores>))),
+        );
+    }
+}
+
+/// Best runs, highest score first
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighScores {
+    entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    pub fn entries(&self) -> &[HighScoreEntry] {
+        &self.entries
+    }
+
+    /// Whether a run with `points` would make it onto the table
+    pub fn qualifies(&self, points: u32) -> bool {
+        points > 0 && (self.entries.len() < MAX_HIGH_SCORES || self.entries.last().is_some_and(|lowest| points > lowest.points))
+    }
+
+    /// Adds `entry` below any equal scores, so older runs keep their place, and drops whatever falls off the end
+    fn insert(&mut self, entry: HighScoreEntry) {
+        let rank = self.entries.partition_point(|existing| existing.points >= entry.points);
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub initials: String,
+    pub points: u32,
+    pub survived_secs: f32,
+    /// Number of insects eaten, by species name
+    pub eaten: BTreeMap<String, u32>,
+    /// Seconds since the Unix epoch when the run ended
+    pub recorded_at: u64,
+}
+
+impl HighScoreEntry {
+    /// Time survived as minutes and seconds
+    pub fn survived(&self) -> String {
+        let secs = self.survived_secs as u32;
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+
+    /// Day the run ended as `YYYY-MM-DD`, in UTC
+    pub fn date(&self) -> String {
+        let (year, month, day) = civil_from_days(self.recorded_at / SECS_PER_DAY);
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+}
+
+/// Converts days since 1970-01-01 to a year, month and day in the Gregorian calendar.
+///
+/// Howard Hinnant's `civil_from_days`, restricted to dates after the epoch
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Months counted from March, so the leap day comes last
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// What happened during the current run, beyond the score
+#[derive(Resource, Default)]
+pub struct RunStats {
+    survived: Duration,
+    eaten: BTreeMap<&'static str, u32>,
+}
+
+/// A run good enough for the table that is waiting for the player's initials
+#[derive(Resource)]
+pub struct PendingHighScore(HighScoreEntry);
+
+impl PendingHighScore {
+    pub fn points(&self) -> u32 {
+        self.0.points
+    }
+
+    /// Puts the run on the table under `initials`
+    pub fn submit(&self, initials: &str, high_scores: &mut HighScores) {
+        high_scores.insert(HighScoreEntry {
+            initials: initials.trim().to_owned(),
+            ..self.0.clone()
+        });
+    }
+}
+
+fn track_survival_time(time: Res<Time>, mut stats: ResMut<RunStats>) {
+    stats.survived += time.delta();
+}
+
+fn count_eaten(mut events: EventReader<EnemyEaten>, mut stats: ResMut<RunStats>) {
+    for event in events.read() {
+        *stats.eaten.entry(event.species.name).or_default() += 1;
+    }
+}
+
+fn check_for_record(mut commands: Commands, score: Res<Score>, stats: Res<RunStats>, high_scores: Res<HighScores>) {
+    if !high_scores.qualifies(score.points()) {
+        return;
+    }
+    let recorded_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+    commands.insert_resource(PendingHighScore(HighScoreEntry {
+        initials: String::new(),
+        points: score.points(),
+        survived_secs: stats.survived.as_secs_f32(),
+        eaten: stats.eaten.iter().map(|(name, count)| (name.to_string(), *count)).collect(),
+        recorded_at,
+    }));
+}
+
+fn discard_pending_record(mut commands: Commands) {
+    commands.remove_resource::<PendingHighScore>();
+}
+
+fn save_high_scores(high_scores: Res<HighScores>) {
+    persistence::save(persistence::data_file(HIGH_SCORES_FILE), high_scores.as_ref());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(initials: &str, points: u32) -> HighScoreEntry {
+        HighScoreEntry {
+            initials: initials.to_owned(),
+            points,
+            survived_secs: 0.0,
+            eaten: BTreeMap::new(),
+            recorded_at: 0,
+        }
+    }
+
+    #[test]
+    fn keeps_best_scores_in_order() {
+        let mut high_scores = HighScores::default();
+        for points in 1..=MAX_HIGH_SCORES as u32 {
+            high_scores.insert(entry("OLD", points * 10));
+        }
+        assert!(!high_scores.qualifies(10));
+        assert!(high_scores.qualifies(11));
+
+        high_scores.insert(entry("NEW", 50));
+        let points: Vec<u32> = high_scores.entries().iter().map(|entry| entry.points).collect();
+        assert_eq!(points, [100, 90, 80, 70, 60, 50, 50, 40, 30, 20]);
+        // Ties go below the existing score
+        assert_eq!(high_scores.entries()[6].initials, "NEW");
+    }
+
+    #[test]
+    fn formats_dates() {
+        let mut high_score = entry("BAT", 1);
+        assert_eq!(high_score.date(), "1970-01-01");
+        high_score.recorded_at = 1_709_208_000;
+        assert_eq!(high_score.date(), "2024-02-29");
+        high_score.survived_secs = 125.7;
+        assert_eq!(high_score.survived(), "2:05");
+    }
+}
@@ -0,0 +1,342 @@
+//! Which keys, mouse buttons and gamepad inputs trigger each action, saved to the config directory
+use std::{collections::BTreeMap, fmt};
+
+use bevy::{input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input_translation::{GameInput, InputTranslationSystem, MenuInput},
+    persistence,
+};
+
+const BINDINGS_FILE: &str = "bindings.ron";
+
+/// How far a stick or trigger must be pushed to count as pressed when bound like a button
+const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+/// Axes checked when a gamepad axis is bound like a button
+const BUTTON_AXES: [GamepadAxis; 6] = [
+    GamepadAxis::LeftStickX,
+    GamepadAxis::LeftStickY,
+    GamepadAxis::RightStickX,
+    GamepadAxis::RightStickY,
+    GamepadAxis::LeftZ,
+    GamepadAxis::RightZ,
+];
+
+#[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct InputSourceSystem;
+
+pub struct InputBindingsPlugin;
+impl Plugin for InputBindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(persistence::load_or_default::<InputBindings>(persistence::config_file(
+            BINDINGS_FILE,
+        )));
+        app.init_resource::<ButtonInput<InputSource>>();
+        app.init_resource::<InputCapture>();
+        app.add_event::<StartRebind>();
+        app.add_event::<ResetBindings>();
+        app.configure_sets(PreUpdate, InputSourceSystem.after(InputSystem));
+        app.add_systems(PreUpdate, update_input_sources.in_set(InputSourceSystem));
+        app.add_systems(
+            PreUpdate,
+            capture_binding
+                .after(InputTranslationSystem)
+                .run_if(|capture: Res<InputCapture>| capture.target().is_some()),
+        );
+        app.add_systems(
+            Update,
+            (
+                start_rebind.run_if(on_event::<StartRebind>),
+                reset_bindings.run_if(on_event::<ResetBindings>),
+            ),
+        );
+        app.add_systems(
+            PostUpdate,
+            save_bindings.run_if(resource_changed::<InputBindings>.and(not(resource_added::<InputBindings>))),
+        );
+    }
+}
+
+/// A single physical input that can be bound to an action
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum InputSource {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+    /// A stick or trigger pushed past `AXIS_PRESS_THRESHOLD`, in the positive or negative direction
+    GamepadAxis {
+        axis: GamepadAxis,
+        positive: bool,
+    },
+}
+
+impl InputSource {
+    fn is_gamepad(&self) -> bool {
+        matches!(self, InputSource::Gamepad(_) | InputSource::GamepadAxis { .. })
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::Key(key) => {
+                let name = format!("{key:?}");
+                let name = name.strip_prefix("Key").or_else(|| name.strip_prefix("Digit")).unwrap_or(&name);
+                write!(f, "{name}")
+            }
+            InputSource::Mouse(button) => write!(f, "Mouse {button:?}"),
+            InputSource::Gamepad(button) => write!(f, "Pad {button:?}"),
+            InputSource::GamepadAxis { axis, positive } => {
+                write!(f, "Pad {axis:?}{}", if *positive { "+" } else { "-" })
+            }
+        }
+    }
+}
+
+/// Inputs bound to each action. Every action can have any number of inputs from any device
+#[derive(Resource, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputBindings {
+    pub game: BTreeMap<GameInput, Vec<InputSource>>,
+    pub menu: BTreeMap<MenuInput, Vec<InputSource>>,
+    pub move_left: Vec<InputSource>,
+    pub move_right: Vec<InputSource>,
+    /// Analog axes added to horizontal movement as they are, negative being left
+    pub horizontal_axes: Vec<GamepadAxis>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use InputSource::{Gamepad as Pad, Key, Mouse};
+        let stick = |axis, positive| InputSource::GamepadAxis { axis, positive };
+        Self {
+            game: BTreeMap::from([
+                (GameInput::Start, vec![Key(KeyCode::Escape), Pad(GamepadButton::Start)]),
+                (
+                    GameInput::Flap,
+                    vec![Key(KeyCode::Space), Pad(GamepadButton::South), Mouse(MouseButton::Left)],
+                ),
+                (
+                    GameInput::Screetch,
+                    vec![Key(KeyCode::ControlLeft), Pad(GamepadButton::West), Mouse(MouseButton::Right)],
+                ),
+            ]),
+            menu: BTreeMap::from([
+                (
+                    MenuInput::Up,
+                    vec![
+                        Key(KeyCode::ArrowUp),
+                        Key(KeyCode::KeyW),
+                        Key(KeyCode::KeyK),
+                        Pad(GamepadButton::DPadUp),
+                        stick(GamepadAxis::LeftStickY, true),
+                    ],
+                ),
+                (
+                    MenuInput::Down,
+                    vec![
+                        Key(KeyCode::ArrowDown),
+                        Key(KeyCode::KeyS),
+                        Key(KeyCode::KeyJ),
+                        Pad(GamepadButton::DPadDown),
+                        stick(GamepadAxis::LeftStickY, false),
+                    ],
+                ),
+                (
+                    MenuInput::Left,
+                    vec![
+                        Key(KeyCode::ArrowLeft),
+                        Key(KeyCode::KeyA),
+                        Key(KeyCode::KeyH),
+                        Pad(GamepadButton::DPadLeft),
+                        stick(GamepadAxis::LeftStickX, false),
+                    ],
+                ),
+                (
+                    MenuInput::Right,
+                    vec![
+                        Key(KeyCode::ArrowRight),
+                        Key(KeyCode::KeyD),
+                        Key(KeyCode::KeyL),
+                        Pad(GamepadButton::DPadRight),
+                        stick(GamepadAxis::LeftStickX, true),
+                    ],
+                ),
+                (
+                    MenuInput::Accept,
+                    vec![Key(KeyCode::Enter), Key(KeyCode::KeyE), Pad(GamepadButton::South)],
+                ),
+                (
+                    MenuInput::Back,
+                    vec![
+                        Key(KeyCode::Escape),
+                        Pad(GamepadButton::East),
+                        Pad(GamepadButton::Select),
+                        Pad(GamepadButton::Start),
+                    ],
+                ),
+            ]),
+            move_left: vec![Key(KeyCode::KeyA)],
+            move_right: vec![Key(KeyCode::KeyD)],
+            horizontal_axes: vec![GamepadAxis::LeftStickX],
+        }
+    }
+}
+
+impl InputBindings {
+    /// Inputs bound to `target`
+    pub fn get(&self, target: BindingTarget) -> &[InputSource] {
+        match target {
+            BindingTarget::Game(input) => self.game.get(&input).map_or(&[], Vec::as_slice),
+            BindingTarget::MoveLeft => &self.move_left,
+            BindingTarget::MoveRight => &self.move_right,
+        }
+    }
+
+    /// Binds `source` to `target`, replacing whatever was bound from the same kind of device.
+    /// Keyboard and mouse count as one kind, gamepad buttons and axes as the other
+    pub fn rebind(&mut self, target: BindingTarget, source: InputSource) {
+        let sources = match target {
+            BindingTarget::Game(input) => self.game.entry(input).or_default(),
+            BindingTarget::MoveLeft => &mut self.move_left,
+            BindingTarget::MoveRight => &mut self.move_right,
+        };
+        sources.retain(|bound| bound.is_gamepad() != source.is_gamepad());
+        sources.push(source);
+    }
+}
+
+/// An action that can be rebound from the controls screen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BindingTarget {
+    Game(GameInput),
+    MoveLeft,
+    MoveRight,
+}
+
+impl BindingTarget {
+    pub const ALL: [BindingTarget; 5] = [
+        BindingTarget::Game(GameInput::Flap),
+        BindingTarget::Game(GameInput::Screetch),
+        BindingTarget::MoveLeft,
+        BindingTarget::MoveRight,
+        BindingTarget::Game(GameInput::Start),
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BindingTarget::Game(GameInput::Start) => "Pause",
+            BindingTarget::Game(GameInput::Flap) => "Flap",
+            BindingTarget::Game(GameInput::Screetch) => "Screetch",
+            BindingTarget::MoveLeft => "Move Left",
+            BindingTarget::MoveRight => "Move Right",
+        }
+    }
+}
+
+/// Action waiting for its next input. Input translation is suspended while this is set
+#[derive(Resource, Default)]
+pub struct InputCapture {
+    target: Option<BindingTarget>,
+}
+
+impl InputCapture {
+    pub fn target(&self) -> Option<BindingTarget> {
+        self.target
+    }
+}
+
+/// Starts waiting for the next pressed input to bind to the target
+#[derive(Event, Clone)]
+pub struct StartRebind(pub BindingTarget);
+
+/// Restores the default bindings
+#[derive(Event, Clone, Default)]
+pub struct ResetBindings;
+
+/// Combines keyboard, mouse and every gamepad so all bound inputs can be checked the same way
+fn update_input_sources(
+    mut sources: ResMut<ButtonInput<InputSource>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+) {
+    let mut pressed: Vec<InputSource> = keys
+        .get_pressed()
+        .map(|key| InputSource::Key(*key))
+        .chain(mouse_buttons.get_pressed().map(|button| InputSource::Mouse(*button)))
+        .collect();
+    for gamepad in gamepads.iter() {
+        pressed.extend(gamepad.get_pressed().map(|button| InputSource::Gamepad(*button)));
+        for axis in BUTTON_AXES {
+            let value = gamepad.get(axis).unwrap_or(0.0);
+            if value.abs() > AXIS_PRESS_THRESHOLD {
+                pressed.push(InputSource::GamepadAxis {
+                    axis,
+                    positive: value > 0.0,
+                });
+            }
+        }
+    }
+
+    sources.clear();
+    let released: Vec<InputSource> = sources.get_pressed().filter(|source| !pressed.contains(source)).copied().collect();
+    for source in released {
+        sources.release(source);
+    }
+    for source in pressed {
+        sources.press(source);
+    }
+}
+
+fn start_rebind(mut events: EventReader<StartRebind>, mut capture: ResMut<InputCapture>) {
+    if let Some(StartRebind(target)) = events.read().last() {
+        capture.target = Some(*target);
+    }
+}
+
+/// Binds the first newly pressed input to the action being captured. Escape cancels
+fn capture_binding(sources: Res<ButtonInput<InputSource>>, mut capture: ResMut<InputCapture>, mut bindings: ResMut<InputBindings>) {
+    let Some(source) = sources.get_just_pressed().next().copied() else {
+        return;
+    };
+    if let Some(target) = capture.target.take() {
+        if source != InputSource::Key(KeyCode::Escape) {
+            bindings.rebind(target, source);
+        }
+    }
+}
+
+fn reset_bindings(mut bindings: ResMut<InputBindings>) {
+    *bindings = InputBindings::default();
+}
+
+fn save_bindings(bindings: Res<InputBindings>) {
+    persistence::save(persistence::config_file(BINDINGS_FILE), bindings.as_ref());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_round_trip() {
+        let mut bindings = InputBindings::default();
+        bindings.rebind(BindingTarget::Game(GameInput::Flap), InputSource::Key(KeyCode::KeyF));
+        let text = ron::to_string(&bindings).unwrap();
+        assert_eq!(ron::from_str::<InputBindings>(&text).unwrap(), bindings);
+    }
+
+    #[test]
+    fn rebind_replaces_same_device() {
+        let mut bindings = InputBindings::default();
+        let target = BindingTarget::Game(GameInput::Flap);
+        bindings.rebind(target, InputSource::Key(KeyCode::KeyF));
+        assert_eq!(
+            bindings.get(target),
+            [InputSource::Gamepad(GamepadButton::South), InputSource::Key(KeyCode::KeyF)]
+        );
+    }
+}
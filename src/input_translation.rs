@@ -1,15 +1,19 @@
-use bevy::{input::InputSystem, prelude::*, window::PrimaryWindow};
+use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
 
-use crate::GameState;
+use crate::{
+    input_bindings::{InputBindings, InputCapture, InputSource, InputSourceSystem},
+    InMenu,
+};
 
-#[derive(Event, PartialEq, Eq)]
+#[derive(Event, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum GameInput {
     Start,
     Flap,
     Screetch,
 }
 
-#[derive(Event, PartialEq, Eq)]
+#[derive(Event, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum MenuInput {
     Up,
     Down,
@@ -27,6 +31,10 @@ impl DirectionalInput {
     pub fn get_normalized(&self) -> f32 {
         self.raw.clamp(-1.0, 1.0)
     }
+    /// Replaces the summed input, for input that does not come from a device such as a replay
+    pub fn set(&mut self, val: f32) {
+        self.raw = val;
+    }
     fn add_left(&mut self) {
         self.raw -= 1.0
     }
@@ -45,7 +53,7 @@ impl DirectionalInput {
 pub struct InputTranslationSystem;
 
 #[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
-struct GameInputSystem;
+pub struct GameInputSystem;
 
 #[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
 struct MenuInputSystem;
@@ -53,134 +61,70 @@ struct MenuInputSystem;
 pub struct InputTranslationPlugin;
 impl Plugin for InputTranslationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            PreUpdate,
-            (
-                process_key_game_input,
-                process_gamepad_game_input,
-                get_keyboard_direction,
-                get_gamepad_direction,
-                process_mouse_game_input,
-            )
-                .in_set(GameInputSystem),
-        );
-        app.add_systems(
-            PreUpdate,
-            (process_key_menu_input, process_gamepad_menu_input).in_set(MenuInputSystem),
-        );
+        app.add_systems(PreUpdate, (translate_game_input, translate_direction).in_set(GameInputSystem));
+        app.add_systems(PreUpdate, translate_menu_input.in_set(MenuInputSystem));
         app.add_systems(PostUpdate, reset_direction);
         app.add_event::<GameInput>();
         app.add_event::<MenuInput>();
         app.init_resource::<DirectionalInput>();
-        app.configure_sets(PreUpdate, MenuInputSystem.run_if(in_menu));
-        app.configure_sets(PreUpdate, GameInputSystem.run_if(not_in_menu));
+        app.configure_sets(PreUpdate, MenuInputSystem.run_if(in_state(InMenu)));
+        app.configure_sets(PreUpdate, GameInputSystem.run_if(not(in_state(InMenu))));
         app.configure_sets(PreUpdate, (MenuInputSystem, GameInputSystem).in_set(InputTranslationSystem));
-        app.configure_sets(PreUpdate, InputTranslationSystem.run_if(window_in_focus).after(InputSystem));
-    }
-}
-
-fn in_menu(game_state: Res<State<GameState>>) -> bool {
-    match game_state.get() {
-        GameState::Gameover => true,
-        _ => false,
+        app.configure_sets(
+            PreUpdate,
+            InputTranslationSystem
+                .run_if(window_in_focus)
+                .run_if(not_capturing)
+                .after(InputSourceSystem),
+        );
     }
-    //TODO Also check paused state
 }
 
-fn not_in_menu(game_state: Res<State<GameState>>) -> bool {
-    !in_menu(game_state)
+/// Without a window, such as when running headless, there is nothing to lose focus
+fn window_in_focus(window: Option<Single<&Window, With<PrimaryWindow>>>) -> bool {
+    window.is_none_or(|window| window.focused)
 }
 
-fn window_in_focus(window: Single<&Window, With<PrimaryWindow>>) -> bool {
-    let window = window.into_inner();
-    window.focused
+/// Inputs are left alone while the controls screen waits for one to bind
+fn not_capturing(capture: Res<InputCapture>) -> bool {
+    capture.target().is_none()
 }
 
-fn get_keyboard_direction(keys: Res<ButtonInput<KeyCode>>, mut direction: ResMut<DirectionalInput>) {
-    if keys.pressed(KeyCode::KeyA) {
-        direction.add_left();
-    }
-    if keys.pressed(KeyCode::KeyD) {
-        direction.add_right();
+fn translate_game_input(mut writer: EventWriter<GameInput>, bindings: Res<InputBindings>, sources: Res<ButtonInput<InputSource>>) {
+    for (input, bound) in bindings.game.iter() {
+        if sources.any_just_pressed(bound.iter().copied()) {
+            writer.send(*input);
+        }
     }
 }
 
-fn process_key_game_input(mut writer: EventWriter<GameInput>, keys: Res<ButtonInput<KeyCode>>) {
-    if keys.just_pressed(KeyCode::Escape) {
-        writer.send(GameInput::Start);
-    }
-    if keys.just_pressed(KeyCode::Space) {
-        writer.send(GameInput::Flap);
-    }
-    if keys.just_pressed(KeyCode::ControlLeft) {
-        writer.send(GameInput::Screetch);
+fn translate_menu_input(mut writer: EventWriter<MenuInput>, bindings: Res<InputBindings>, sources: Res<ButtonInput<InputSource>>) {
+    for (input, bound) in bindings.menu.iter() {
+        if sources.any_just_pressed(bound.iter().copied()) {
+            writer.send(*input);
+        }
     }
 }
 
-fn process_key_menu_input(mut writer: EventWriter<MenuInput>, keys: Res<ButtonInput<KeyCode>>) {
-    if keys.just_pressed(KeyCode::Enter) {
-        writer.send(MenuInput::Accept);
-    }
-    //TODO rest of inputs
-}
-fn get_gamepad_direction(gamepads: Query<&Gamepad>, mut direction: ResMut<DirectionalInput>) {
-    for gamepad in gamepads.iter() {
-        let input_direction = gamepad.left_stick().x;
-        direction.add_value(input_direction);
+fn translate_direction(
+    bindings: Res<InputBindings>,
+    sources: Res<ButtonInput<InputSource>>,
+    gamepads: Query<&Gamepad>,
+    mut direction: ResMut<DirectionalInput>,
+) {
+    if sources.any_pressed(bindings.move_left.iter().copied()) {
+        direction.add_left();
     }
-}
-
-fn process_gamepad_game_input(mut writer: EventWriter<GameInput>, gamepads: Query<&Gamepad>) {
-    for gamepad in gamepads.iter() {
-        if gamepad.just_pressed(GamepadButton::Start) {
-            writer.send(GameInput::Start);
-        }
-        if gamepad.just_pressed(GamepadButton::South) {
-            writer.send(GameInput::Flap);
-        }
-        if gamepad.just_pressed(GamepadButton::West) {
-            println!("West");
-            writer.send(GameInput::Screetch);
-        }
+    if sources.any_pressed(bindings.move_right.iter().copied()) {
+        direction.add_right();
     }
-}
-fn process_gamepad_menu_input(mut writer: EventWriter<MenuInput>, gamepads: Query<&Gamepad>) {
     for gamepad in gamepads.iter() {
-        if gamepad.just_pressed(GamepadButton::South) {
-            writer.send(MenuInput::Accept);
+        for axis in bindings.horizontal_axes.iter() {
+            direction.add_value(gamepad.get(*axis).unwrap_or(0.0));
         }
-        //TODO Rest of inputs
-    }
-}
-fn process_mouse_game_input(mut writer: EventWriter<GameInput>, mouse_buttons: Res<ButtonInput<MouseButton>>) {
-    if mouse_buttons.just_pressed(MouseButton::Left) {
-        writer.send(GameInput::Flap);
-    }
-    if mouse_buttons.just_pressed(MouseButton::Right) {
-        writer.send(GameInput::Screetch);
     }
 }
 
-fn process_key_menu_navigation(mut writer: EventWriter<MenuInput>, keys: Res<ButtonInput<KeyCode>>) {
-    if keys.any_just_pressed([KeyCode::ArrowUp, KeyCode::KeyW, KeyCode::KeyK]) {
-        writer.send(MenuInput::Up);
-    }
-    if keys.any_just_pressed([KeyCode::ArrowLeft, KeyCode::KeyA, KeyCode::KeyH]) {
-        writer.send(MenuInput::Left);
-    }
-    if keys.any_just_pressed([KeyCode::ArrowRight, KeyCode::KeyD, KeyCode::KeyL]) {
-        writer.send(MenuInput::Right);
-    }
-    if keys.any_just_pressed([KeyCode::ArrowDown, KeyCode::KeyS, KeyCode::KeyJ]) {
-        writer.send(MenuInput::Down);
-    }
-    if keys.any_just_pressed([KeyCode::Enter, KeyCode::KeyE]) {
-        writer.send(MenuInput::Accept);
-    }
-    if keys.just_pressed(KeyCode::Escape) {
-        writer.send(MenuInput::Back);
-    }
-}
 fn reset_direction(mut direction: ResMut<DirectionalInput>) {
     direction.reset()
 }
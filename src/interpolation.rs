@@ -0,0 +1,119 @@
+//! Smooths movement between fixed gameplay ticks.
+//!
+//! Gameplay moves `Transform`s in `FixedUpdate`, which rarely lines up with the frame rate. Entities with
+//! `Interpolated` are drawn part way between their last two tick positions, then put back before the next tick so
+//! gameplay never sees the in-between position
+use bevy::{
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+};
+
+use crate::constants::WINDOW_DIMENSIONS;
+
+pub struct InterpolationPlugin;
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            RunFixedMainLoop,
+            (
+                restore_tick_translation.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+                interpolate_translation.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+            ),
+        );
+        app.add_systems(FixedFirst, store_previous_translation);
+        app.add_systems(FixedLast, store_current_translation);
+    }
+}
+
+/// Draws this entity between its translations from the last two fixed ticks
+#[derive(Component, Default)]
+#[component(on_add = start_at_translation)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+    /// Translation last drawn, used to tell whether something outside the fixed ticks moved the entity since
+    rendered: Option<Vec3>,
+}
+
+/// Starts both tick translations where the entity is, so something spawned part way through the ticks isn't drawn
+/// coming from the origin
+fn start_at_translation(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    let Some(translation) = world.get::<Transform>(entity).map(|transform| transform.translation) else {
+        return;
+    };
+    if let Some(mut interpolated) = world.get_mut::<Interpolated>(entity) {
+        interpolated.previous = translation;
+        interpolated.current = translation;
+    }
+}
+
+/// Puts interpolated entities back where the last tick left them, unless they were moved outside of the fixed ticks
+/// (for example by a reset), in which case they stay put and are not interpolated from their old position
+fn restore_tick_translation(mut query: Query<(&mut Transform, &mut Interpolated)>) {
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        if interpolated.rendered == Some(transform.translation) {
+            transform.translation = interpolated.current;
+        } else {
+            interpolated.previous = transform.translation;
+            interpolated.current = transform.translation;
+        }
+    }
+}
+
+fn store_previous_translation(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.previous = transform.translation;
+    }
+}
+
+fn store_current_translation(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.current = transform.translation;
+    }
+}
+
+fn interpolate_translation(fixed_time: Res<Time<Fixed>>, mut query: Query<(&mut Transform, &mut Interpolated)>) {
+    let fraction = fixed_time.overstep_fraction();
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        let Interpolated { previous, current, .. } = *interpolated;
+        // Anything that moved half the window in one tick wrapped around the edge, so don't draw it sweeping across
+        transform.translation = if (current.x - previous.x).abs() > WINDOW_DIMENSIONS.x / 2.0 {
+            current
+        } else {
+            previous.lerp(current, fraction)
+        };
+        interpolated.rendered = Some(transform.translation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    #[test]
+    fn entities_spawned_during_a_tick_are_drawn_where_they_spawned() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InterpolationPlugin));
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        // Leaves half a tick over each frame, so an entity coming from the origin would be drawn half way there
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep.mul_f32(1.5)));
+        app.add_systems(FixedUpdate, |mut commands: Commands, mut spawned: Local<bool>| {
+            if !*spawned {
+                commands.spawn((Interpolated::default(), Transform::from_xyz(600.0, -200.0, 0.0)));
+                *spawned = true;
+            }
+        });
+
+        for _ in 0..10 {
+            app.update();
+            let world = app.world_mut();
+            if let Ok(transform) = world.query_filtered::<&Transform, With<Interpolated>>().get_single(world) {
+                assert_eq!(transform.translation, Vec3::new(600.0, -200.0, 0.0));
+                return;
+            }
+        }
+        panic!("Nothing was spawned");
+    }
+}
@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::horizontal_delta,
+    echolocation::Revealed,
+    enemy::Enemy,
+    game::{EnemyEaten, GameplaySystem, ResetAppExt},
+    pause_menu::PausedState,
+    player::Player,
+    GameState,
+};
+
+/// Distance the bat can see in total darkness
+const MIN_SIGHT_RADIUS: f32 = 120.0;
+/// Distance the bat can see in full light, enough to cover the whole window
+const MAX_SIGHT_RADIUS: f32 = 1600.0;
+
+pub struct LightingPlugin;
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LightCurve>();
+        app.init_resetting_resource::<WorldLight>();
+        app.add_systems(
+            FixedUpdate,
+            (advance_time, restore_light.run_if(on_event::<EnemyEaten>))
+                .chain()
+                .in_set(LightingSystem)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PausedState::Unpaused)),
+        );
+        app.add_systems(PostUpdate, (track_base_colors, tint_sprites, limit_sight).chain());
+        app.configure_sets(FixedUpdate, LightingSystem.in_set(GameplaySystem));
+    }
+}
+
+/// Systems that change `WorldLight`. Systems reacting to the light level should run after this
+#[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct LightingSystem;
+
+/// How bright the world is over the course of a run, as `(seconds, level)` keyframes.
+///
+/// Levels are between 0.0 (pitch black) and 1.0 (full light) and are linearly interpolated between keyframes.
+#[derive(Resource)]
+pub struct LightCurve {
+    keyframes: Vec<(f32, f32)>,
+}
+
+impl Default for LightCurve {
+    fn default() -> Self {
+        Self::new(vec![(0.0, 1.0), (20.0, 1.0), (90.0, 0.35), (180.0, 0.1)])
+    }
+}
+
+impl LightCurve {
+    /// Keyframes do not need to be sorted
+    pub fn new(mut keyframes: Vec<(f32, f32)>) -> Self {
+        keyframes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { keyframes }
+    }
+
+    /// Light level `secs` into a run
+    pub fn level_at(&self, secs: f32) -> f32 {
+        let Some(&(first_secs, first_level)) = self.keyframes.first() else {
+            return 1.0;
+        };
+        if secs <= first_secs {
+            return first_level;
+        }
+        for window in self.keyframes.windows(2) {
+            let ((start_secs, start_level), (end_secs, end_level)) = (window[0], window[1]);
+            if secs <= end_secs {
+                let progress = (secs - start_secs) / (end_secs - start_secs);
+                return start_level.lerp(end_level, progress);
+            }
+        }
+        self.keyframes.last().map_or(1.0, |keyframe| keyframe.1)
+    }
+}
+
+/// Current brightness of the world, driven by `LightCurve`
+#[derive(Resource)]
+pub struct WorldLight {
+    /// Position along the light curve in seconds. Eating light sources moves this backwards
+    curve_secs: f32,
+    level: f32,
+}
+
+impl FromWorld for WorldLight {
+    fn from_world(world: &mut World) -> Self {
+        let curve = world.resource::<LightCurve>();
+        Self {
+            curve_secs: 0.0,
+            level: curve.level_at(0.0),
+        }
+    }
+}
+
+impl WorldLight {
+    /// Between 0.0 (pitch black) and 1.0 (full light)
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Distance from the bat within which enemies are visible without echolocation
+    pub fn sight_radius(&self) -> f32 {
+        MIN_SIGHT_RADIUS.lerp(MAX_SIGHT_RADIUS, self.level)
+    }
+
+    fn set_curve_secs(&mut self, secs: f32, curve: &LightCurve) {
+        self.curve_secs = secs.max(0.0);
+        self.level = curve.level_at(self.curve_secs);
+    }
+}
+
+/// Color a sprite has without lighting, and the color lighting last gave it.
+///
+/// Lighting works from the base, so it never darkens a sprite twice. When something else changes the sprite's color,
+/// such as a particle fading or a palette change, that color becomes the new base
+#[derive(Component)]
+struct BaseColor {
+    base: Color,
+    lit: Option<LinearRgba>,
+}
+
+impl BaseColor {
+    fn new(base: Color) -> Self {
+        Self { base, lit: None }
+    }
+
+    /// Color for a sprite currently colored `current`, at `brightness`. Alpha is left alone
+    fn light(&mut self, current: Color, brightness: f32) -> Color {
+        let current_linear = current.to_linear();
+        // Only the alpha may have changed since lighting last set it, as sprites fading in and out is left to others
+        if self.lit.is_none_or(|lit| lit.with_alpha(current_linear.alpha) != current_linear) {
+            self.base = current;
+        }
+        let base = self.base.to_linear();
+        let lit = LinearRgba::new(
+            base.red * brightness,
+            base.green * brightness,
+            base.blue * brightness,
+            current_linear.alpha,
+        );
+        self.lit = Some(lit);
+        lit.into()
+    }
+}
+
+fn advance_time(time: Res<Time>, curve: Res<LightCurve>, mut light: ResMut<WorldLight>) {
+    let secs = light.curve_secs + time.delta_secs();
+    light.set_curve_secs(secs, &curve);
+}
+
+/// Winds the light curve back for every light source eaten
+fn restore_light(mut events: EventReader<EnemyEaten>, curve: Res<LightCurve>, mut light: ResMut<WorldLight>) {
+    for event in events.read() {
+        let secs = light.curve_secs - event.species.light_restored_secs;
+        light.set_curve_secs(secs, &curve);
+    }
+}
+
+fn track_base_colors(mut commands: Commands, query: Query<(Entity, &Sprite), Without<BaseColor>>) {
+    for (entity, sprite) in query.iter() {
+        commands.entity(entity).insert(BaseColor::new(sprite.color));
+    }
+}
+
+/// Darkens every sprite according to the light level, leaving alpha alone. Revealed sprites stay fully lit
+fn tint_sprites(light: Res<WorldLight>, mut query: Query<(&mut Sprite, &mut BaseColor, Has<Revealed>)>) {
+    for (mut sprite, mut base_color, revealed) in query.iter_mut() {
+        let brightness = if revealed { 1.0 } else { light.level() };
+        sprite.color = base_color.light(sprite.color, brightness);
+    }
+}
+
+/// Hides enemies too far from the bat to be seen, unless they were revealed by echolocation
+fn limit_sight(
+    light: Res<WorldLight>,
+    player: Single<&Transform, With<Player>>,
+    mut enemies: Query<(&Transform, &mut Visibility, Has<Revealed>), With<Enemy>>,
+) {
+    let origin = player.into_inner().translation.truncate();
+    let sight_radius = light.sight_radius();
+    for (transform, mut visibility, revealed) in enemies.iter_mut() {
+        let position = transform.translation.truncate();
+        let delta = Vec2::new(horizontal_delta(origin.x, position.x, true), position.y - origin.y);
+        let visible = revealed || delta.length() <= sight_radius;
+        visibility.set_if_neq(if visible { Visibility::Inherited } else { Visibility::Hidden });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curve_interpolates() {
+        let curve = LightCurve::new(vec![(10.0, 0.0), (0.0, 1.0)]);
+        assert_eq!(curve.level_at(-1.0), 1.0);
+        assert_eq!(curve.level_at(5.0), 0.5);
+        assert_eq!(curve.level_at(20.0), 0.0);
+    }
+
+    #[test]
+    fn lighting_keeps_color_changes_made_by_others() {
+        let mut base_color = BaseColor::new(Color::WHITE);
+        let lit = base_color.light(Color::WHITE, 0.5);
+        assert_eq!(lit, LinearRgba::new(0.5, 0.5, 0.5, 1.0).into());
+        // Lighting its own output again doesn't darken it further, and fading it out keeps the base
+        let faded = lit.with_alpha(0.25);
+        assert_eq!(base_color.light(faded, 0.5), LinearRgba::new(0.5, 0.5, 0.5, 0.25).into());
+        // Anything else recoloring the sprite gives it a new base
+        let recolored = LinearRgba::new(1.0, 0.0, 0.0, 1.0).into();
+        assert_eq!(base_color.light(recolored, 0.5), LinearRgba::new(0.5, 0.0, 0.0, 1.0).into());
+        // And lighting up again goes back to that base
+        assert_eq!(base_color.light(LinearRgba::new(0.5, 0.0, 0.0, 1.0).into(), 1.0), recolored);
+    }
+}
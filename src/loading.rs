@@ -0,0 +1,237 @@
+use bevy::{
+    asset::{LoadState, RecursiveDependencyLoadState},
+    prelude::*,
+};
+
+use crate::GameState;
+
+/// Explains how to get assets that cannot be distributed with the source
+const ASSET_DISTRIBUTION_NOTE: &str = "assets/asset_distribution_note.md";
+
+pub struct LoadingPlugin;
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingAssets>();
+        app.enable_state_scoped_entities::<GameState>();
+        app.add_systems(OnEnter(GameState::Loading), spawn_loading_screen);
+        app.add_systems(Update, check_loading.run_if(in_state(GameState::Loading)));
+    }
+}
+
+/// Handles that must finish loading before leaving `GameState::Loading`
+#[derive(Resource, Default)]
+pub struct LoadingAssets {
+    handles: Vec<UntypedHandle>,
+    /// Handles that must load, but whose dependencies may fail because something else substitutes for them
+    handles_with_optional_dependencies: Vec<UntypedHandle>,
+    /// Handles whose failure is not fatal because something else substitutes for them
+    optional_handles: Vec<UntypedHandle>,
+    failed: bool,
+}
+
+enum LoadingProgress {
+    Loading { loaded: usize, total: usize },
+    Done,
+    Failed { path: String, reason: String },
+}
+
+impl LoadingAssets {
+    pub fn track<A: Asset>(&mut self, handle: &Handle<A>) {
+        self.handles.push(handle.clone().untyped());
+    }
+
+    /// Waits for `handle` and everything it depends on to finish loading, but only fails if `handle` itself does
+    pub fn track_with_optional_dependencies<A: Asset>(&mut self, handle: &Handle<A>) {
+        self.handles_with_optional_dependencies.push(handle.clone().untyped());
+    }
+
+    /// Waits for `handle` to finish loading, but carries on if it fails
+    pub fn track_optional<A: Asset>(&mut self, handle: &Handle<A>) {
+        self.optional_handles.push(handle.clone().untyped());
+    }
+
+    fn tracked(&self) -> usize {
+        self.handles.len() + self.handles_with_optional_dependencies.len() + self.optional_handles.len()
+    }
+
+    fn progress(&self, asset_server: &AssetServer) -> LoadingProgress {
+        for handle in self.handles.iter().chain(self.handles_with_optional_dependencies.iter()) {
+            if let Some(LoadState::Failed(err)) = asset_server.get_load_state(handle.id()) {
+                let path = handle.path().map_or_else(|| "unknown asset".to_owned(), |path| path.to_string());
+                return LoadingProgress::Failed {
+                    path,
+                    reason: err.to_string(),
+                };
+            }
+        }
+        let mut loaded = 0;
+        for handle in self.handles.iter() {
+            if asset_server.is_loaded_with_dependencies(handle.id()) {
+                loaded += 1;
+            }
+        }
+        for handle in self.handles_with_optional_dependencies.iter() {
+            if let Some((LoadState::Loaded, _, RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed(_))) =
+                asset_server.get_load_states(handle.id())
+            {
+                loaded += 1;
+            }
+        }
+        for handle in self.optional_handles.iter() {
+            if let Some(LoadState::Loaded | LoadState::Failed(_)) = asset_server.get_load_state(handle.id()) {
+                loaded += 1;
+            }
+        }
+        let total = self.tracked();
+        if loaded == total {
+            LoadingProgress::Done
+        } else {
+            LoadingProgress::Loading { loaded, total }
+        }
+    }
+}
+
+fn check_loading(
+    asset_server: Res<AssetServer>,
+    mut loading: ResMut<LoadingAssets>,
+    mut next_state: ResMut<NextState<GameState>>,
+    status_text: Single<&mut Text, With<LoadingStatusText>>,
+) {
+    if loading.failed {
+        return;
+    }
+    let mut text = status_text.into_inner();
+    match loading.progress(&asset_server) {
+        LoadingProgress::Loading { loaded, total } => {
+            **text = format!("Loading... {loaded}/{total}");
+        }
+        LoadingProgress::Done => {
+            info!("Loaded {} assets", loading.tracked());
+            next_state.set(GameState::MainMenu);
+        }
+        LoadingProgress::Failed { path, reason } => {
+            error!("Failed to load {path}: {reason}");
+            **text = format!(
+                "Could not load \"{path}\"\n\n{reason}\n\nSome assets cannot be distributed with the source code. \
+                 See {ASSET_DISTRIBUTION_NOTE} for how to get them."
+            );
+            loading.failed = true;
+        }
+    }
+}
+
+fn spawn_loading_screen(mut commands: Commands) {
+    commands.spawn(LoadingScreen).with_children(|parent| {
+        parent.spawn(LoadingStatusText);
+    });
+}
+
+#[derive(Component)]
+#[require(Text(|| Text::new("Loading...")))]
+struct LoadingStatusText;
+
+#[derive(Component)]
+#[require(Node(Self::node), BackgroundColor(Self::background_color), StateScoped<GameState>(|| StateScoped(GameState::Loading)))]
+struct LoadingScreen;
+
+impl LoadingScreen {
+    fn node() -> Node {
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            padding: UiRect::all(Val::Px(48.0)),
+            ..default()
+        }
+    }
+    fn background_color() -> BackgroundColor {
+        BackgroundColor(Color::BLACK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread, time::Duration};
+
+    use bevy::{
+        asset::{io::Reader, AssetLoader, AssetPlugin, LoadContext},
+        state::app::StatesPlugin,
+    };
+
+    use super::*;
+    use crate::sprite_sheet::{SpriteSheet, SpriteSheetPlugin};
+
+    /// Stands in for the image loader, which needs the render plugins. Any file it is given loads as a blank image
+    struct BlankImageLoader;
+
+    impl AssetLoader for BlankImageLoader {
+        type Asset = Image;
+        type Settings = ();
+        type Error = std::io::Error;
+
+        async fn load(&self, _reader: &mut dyn Reader, _settings: &(), _load_context: &mut LoadContext<'_>) -> Result<Image, Self::Error> {
+            Ok(Image::default())
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["png"]
+        }
+    }
+
+    fn loading_app(asset_plugin: AssetPlugin) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, asset_plugin));
+        app.init_asset::<Image>();
+        app.init_asset::<TextureAtlasLayout>();
+        app.add_plugins((LoadingPlugin, SpriteSheetPlugin));
+        app.insert_state(GameState::Loading);
+        app.add_systems(Startup, |asset_server: Res<AssetServer>, mut loading: ResMut<LoadingAssets>| {
+            let sheet: Handle<SpriteSheet> = asset_server.load("sprites/bat.sheet.ron");
+            loading.track_with_optional_dependencies(&sheet);
+        });
+        app
+    }
+
+    /// Updates `app` until its tracked assets are done loading, returning false if they take more than 5 seconds
+    fn finish_loading(app: &mut App) -> bool {
+        for _ in 0..500 {
+            app.update();
+            let progress = app
+                .world()
+                .resource::<LoadingAssets>()
+                .progress(app.world().resource::<AssetServer>());
+            if matches!(progress, LoadingProgress::Done) {
+                app.update();
+                return *app.world().resource::<State<GameState>>().get() == GameState::MainMenu;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    /// The bat's strip image isn't distributed, and no image loader is added here either, so its sheet loads without it
+    #[test]
+    fn sheet_without_its_image_still_finishes_loading() {
+        let mut app = loading_app(AssetPlugin::default());
+        assert!(finish_loading(&mut app), "Still loading after 5 seconds");
+    }
+
+    #[test]
+    fn sheet_with_its_image_finishes_loading() {
+        let directory = std::env::temp_dir().join(format!("bat-game-loading-test-{}", std::process::id()));
+        fs::create_dir_all(directory.join("sprites")).unwrap();
+        fs::copy("assets/sprites/bat.sheet.ron", directory.join("sprites/bat.sheet.ron")).unwrap();
+        fs::write(directory.join("sprites/nf_batFlightStrip.png"), []).unwrap();
+
+        let mut app = loading_app(AssetPlugin {
+            file_path: directory.to_string_lossy().into_owned(),
+            ..default()
+        });
+        app.register_asset_loader(BlankImageLoader);
+        let finished = finish_loading(&mut app);
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(finished, "Still loading after 5 seconds");
+        assert_eq!(app.world().resource::<Assets<Image>>().len(), 1);
+    }
+}
@@ -1,83 +1,164 @@
 use bevy::prelude::*;
-use bevy::window::WindowMode;
 use game_over::GameOverPlugin;
 use pause_menu::PauseMenuPlugin;
 use pause_menu::PausedState;
 
+use crate::audio::MusicPlugin;
 use crate::audio::SoundPlugin;
 use crate::background::BackgroundPlugin;
 use crate::camera::CameraPlugin;
+use crate::collision::CollisionPlugin;
 use crate::constants::GAME_NAME;
 use crate::constants::WINDOW_DIMENSIONS;
+use crate::controls_menu::ControlsMenuPlugin;
+use crate::echolocation::EcholocationPlugin;
+use crate::enemy::EnemyPlugin;
 use crate::game::GamePlugin;
+use crate::high_score_menu::HighScoreMenuPlugin;
+use crate::high_scores::HighScoresPlugin;
+use crate::input_bindings::InputBindingsPlugin;
 use crate::input_translation::InputTranslationPlugin;
+use crate::interpolation::InterpolationPlugin;
+use crate::lighting::LightingPlugin;
+use crate::loading::LoadingPlugin;
+use crate::main_menu::MainMenuPlugin;
+use crate::particles::ParticlePlugin;
+use crate::placeholder::PlaceholderPlugin;
+use crate::replay::ReplayPlugin;
+use crate::score::ScorePlugin;
+use crate::settings::Settings;
+use crate::settings::SettingsPlugin;
+use crate::settings_menu::SettingsMenuPlugin;
+use crate::sprite_sheet::SpriteSheetPlugin;
+use crate::ui::FocusPlugin;
 
 mod animation;
 mod audio;
 mod background;
 mod camera;
+mod collision;
 mod constants;
+mod controls_menu;
+mod echolocation;
+mod enemy;
 mod game;
 mod game_over;
+mod high_score_menu;
+mod high_scores;
+mod input_bindings;
 mod input_translation;
+mod interpolation;
+mod lighting;
+mod loading;
+mod main_menu;
+mod particles;
 mod pause_menu;
+mod persistence;
 mod physics;
+mod placeholder;
 mod player;
+mod replay;
+mod score;
+mod settings;
+mod settings_menu;
+mod sprite_sheet;
+#[cfg(test)]
+mod test_harness;
 mod ui;
 
+/// Argument that turns on reloading assets when their files change, in debug builds
+const HOT_RELOAD_ARG: &str = "--hot-reload";
+
 #[derive(States, Clone, Copy, Eq, PartialEq, Hash, Debug)]
-enum GameState {
+pub enum GameState {
+    Loading,
+    MainMenu,
     Ready,
     Gameover,
     Playing,
 }
 
+/// Exists while a menu is open and menu input should be used instead of game input
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 struct InMenu;
 
 impl ComputedStates for InMenu {
-    type SourceStates = PausedState;
+    type SourceStates = (GameState, PausedState);
 
-    fn compute(sources: PausedState) -> Option<Self> {
+    fn compute(sources: (GameState, PausedState)) -> Option<Self> {
         match sources {
-            PausedState::Unpaused => None,
-            PausedState::Paused => Some(InMenu),
+            (GameState::Loading | GameState::MainMenu | GameState::Gameover, _) | (_, PausedState::Paused) => Some(InMenu),
+            _ => None,
         }
     }
 }
 
 fn main() {
     let mut app = App::new();
-    let window_mode = if cfg!(debug_assertions) {
-        WindowMode::Windowed
-    } else {
-        // Current and Primary do not work on Wayland
-        WindowMode::BorderlessFullscreen(MonitorSelection::Index(0))
-    };
+    // Loaded before the plugins are added, so the window opens with the saved mode
+    let settings = Settings::load();
     // Bevy Plugins
     let window = Window {
         title: GAME_NAME.into(),
         name: Some(GAME_NAME.into()),
         resolution: WINDOW_DIMENSIONS.into(),
         resizable: false,
-        mode: window_mode,
+        mode: settings.window_mode(),
+        present_mode: settings.present_mode(),
         ..default()
     };
     // Set default_nearest to prevent blurry sprits
-    app.add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()).set(WindowPlugin {
-        primary_window: Some(window),
-        ..default()
-    }));
+    app.add_plugins(
+        DefaultPlugins
+            .set(ImagePlugin::default_nearest())
+            .set(WindowPlugin {
+                primary_window: Some(window),
+                ..default()
+            })
+            .set(AssetPlugin {
+                // Reloads assets when their files change, to try out art without restarting
+                watch_for_changes_override: Some(cfg!(debug_assertions) && std::env::args().any(|arg| arg == HOT_RELOAD_ARG)),
+                ..default()
+            }),
+    );
+    app.insert_resource(settings);
     // My Plugins
     app.add_plugins((
+        LoadingPlugin,
+        PlaceholderPlugin,
+        SpriteSheetPlugin,
         CameraPlugin,
         BackgroundPlugin,
-        GamePlugin,
+        InputBindingsPlugin,
         InputTranslationPlugin,
+        ReplayPlugin,
+        SettingsPlugin,
         SoundPlugin,
+        MusicPlugin,
+    ));
+    // Gameplay
+    app.add_plugins((
+        GamePlugin,
+        EnemyPlugin,
+        CollisionPlugin,
+        InterpolationPlugin,
+        ScorePlugin,
+        EcholocationPlugin,
+        LightingPlugin,
+        ParticlePlugin,
+        HighScoresPlugin,
+    ));
+    // Menus
+    app.add_plugins((
         PauseMenuPlugin,
         GameOverPlugin,
+        MainMenuPlugin,
+        SettingsMenuPlugin,
+        ControlsMenuPlugin,
+        HighScoreMenuPlugin,
+        FocusPlugin,
     ));
-    app.insert_state(GameState::Ready);
+    app.insert_state(GameState::Loading);
+    app.add_computed_state::<InMenu>();
     app.run();
 }
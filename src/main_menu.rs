@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+
+use crate::{
+    game::Reset,
+    input_translation::{InputTranslationSystem, MenuInput},
+    ui::{AcceptAction, Focusable, Focused, MenuActionAppExt},
+    GameState,
+};
+
+const TITLE: &str = "Flappy Bat";
+
+pub struct MainMenuPlugin;
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<MenuScreen>();
+        app.enable_state_scoped_entities::<MenuScreen>();
+        app.add_event::<OpenMenuScreen>();
+        app.add_menu_action::<Reset>();
+        app.add_menu_action::<OpenMenuScreen>();
+        app.add_menu_action::<AppExit>();
+        app.add_systems(
+            PreUpdate,
+            back_to_title
+                .after(InputTranslationSystem)
+                .run_if(in_state(GameState::MainMenu))
+                .run_if(not(in_state(MenuScreen::Title))),
+        );
+        app.add_systems(Update, open_menu_screen.run_if(on_event::<OpenMenuScreen>));
+        // Spawned on enter rather than at startup, because the initial state is entered before `Startup` runs
+        app.add_systems(OnEnter(MenuScreen::Title), spawn_title_screen);
+    }
+}
+
+/// Screen shown while in `GameState::MainMenu`
+#[derive(SubStates, Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+#[source(GameState = GameState::MainMenu)]
+pub enum MenuScreen {
+    #[default]
+    Title,
+    Settings,
+    Controls,
+    HighScores,
+}
+
+/// Switches the main menu to another screen
+#[derive(Event, Clone)]
+pub struct OpenMenuScreen(pub MenuScreen);
+
+fn open_menu_screen(mut events: EventReader<OpenMenuScreen>, mut next_state: ResMut<NextState<MenuScreen>>) {
+    if let Some(event) = events.read().last() {
+        next_state.set(event.0);
+    }
+}
+
+/// Returns from any other screen to the title screen
+fn back_to_title(mut events: EventReader<MenuInput>, mut next_state: ResMut<NextState<MenuScreen>>) {
+    if events.read().any(|event| *event == MenuInput::Back) {
+        next_state.set(MenuScreen::Title);
+    }
+}
+
+fn spawn_title_screen(mut commands: Commands) {
+    commands.spawn(TitleScreen).with_children(|parent| {
+        parent.spawn(TitleText);
+        parent.spawn((TitlePlayButton, Focused));
+        parent.spawn((
+            TitleButton,
+            Text::new("Settings"),
+            AcceptAction::new(OpenMenuScreen(MenuScreen::Settings)),
+        ));
+        parent.spawn((
+            TitleButton,
+            Text::new("Controls"),
+            AcceptAction::new(OpenMenuScreen(MenuScreen::Controls)),
+        ));
+        parent.spawn((
+            TitleButton,
+            Text::new("High Scores"),
+            AcceptAction::new(OpenMenuScreen(MenuScreen::HighScores)),
+        ));
+        parent.spawn((TitleButton, Text::new("Quit"), AcceptAction::<AppExit>::default()));
+    });
+}
+
+#[derive(Component)]
+#[require(Text(|| Text::new(TITLE)), TextFont(Self::font))]
+struct TitleText;
+
+impl TitleText {
+    fn font() -> TextFont {
+        TextFont {
+            font_size: 64.0,
+            ..default()
+        }
+    }
+}
+
+#[derive(Component, Default)]
+#[require(Focusable, Node(Self::node))]
+struct TitleButton;
+
+impl TitleButton {
+    fn node() -> Node {
+        Node {
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+#[require(TitleButton, AcceptAction<Reset>, Text(|| Text::new("Play")))]
+struct TitlePlayButton;
+
+#[derive(Component)]
+#[require(Node(Self::node), BackgroundColor(Self::background_color), StateScoped<MenuScreen>(|| StateScoped(MenuScreen::Title)))]
+struct TitleScreen;
+
+impl TitleScreen {
+    fn node() -> Node {
+        Node {
+            width: Val::Percent(30.0),
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            align_self: AlignSelf::Center,
+            justify_self: JustifySelf::Center,
+            row_gap: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(24.0)),
+            ..default()
+        }
+    }
+    fn background_color() -> BackgroundColor {
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.85))
+    }
+}
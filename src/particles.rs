@@ -0,0 +1,310 @@
+//! Small sprites thrown out by flapping, eating and crashing.
+//!
+//! Particles are only for show, so they use `thread_rng` rather than `GameRng` and never change how a run plays out
+use std::{
+    f32::consts::{FRAC_PI_2, PI},
+    ops::Range,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use rand::{thread_rng, Rng};
+
+use crate::{
+    constants::WINDOW_BOTTOM,
+    game::{DespawnOnReset, EnemyEaten},
+    game_over::{GameOver, GameOverReason},
+    pause_menu::PausedState,
+    physics::Gravity,
+    player::{Player, PlayerFlapped},
+    settings::Settings,
+};
+
+/// Drawn in front of the bat and insects
+const PARTICLE_Z: f32 = 1.0;
+const WING_DUST_COUNT: u32 = 6;
+const INSECT_BITS_COUNT: u32 = 10;
+const CRASH_SPLASH_COUNT: u32 = 24;
+/// Mist particles per second, for `CRASH_MIST_SECS` after a crash
+const CRASH_MIST_RATE: f32 = 60.0;
+const CRASH_MIST_SECS: f32 = 0.4;
+
+pub struct ParticlePlugin;
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (emit_wing_dust, emit_insect_bits, emit_crash_splash),
+                (emit_particles, update_particles).run_if(in_state(PausedState::Unpaused)),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// How a kind of particle looks and moves
+pub struct ParticleEffect {
+    /// Seconds each particle lives for, picked at random from the range
+    pub lifetime: Range<f32>,
+    /// Starting speed in logical pixels per second, picked at random from the range
+    pub speed: Range<f32>,
+    /// Angle particles are thrown at, in radians counterclockwise from the right
+    pub direction: f32,
+    /// Particles are thrown up to this many radians either side of `direction`
+    pub spread: f32,
+    /// Downward acceleration, given to each particle as its `Gravity`
+    pub gravity: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+    /// Width of the square particle when it appears and when it disappears
+    pub start_size: f32,
+    pub end_size: f32,
+}
+
+/// Dust shaken off the wings, drifting down from the bat
+pub static WING_DUST: ParticleEffect = ParticleEffect {
+    lifetime: 0.3..0.6,
+    speed: 40.0..120.0,
+    direction: -FRAC_PI_2,
+    spread: 0.8,
+    gravity: 150.0,
+    start_color: Color::srgba(0.8, 0.75, 0.7, 0.6),
+    end_color: Color::srgba(0.8, 0.75, 0.7, 0.0),
+    start_size: 4.0,
+    end_size: 1.0,
+};
+
+/// What is left of an eaten insect, flying off in every direction. Colored after the insect when emitted
+pub static INSECT_BITS: ParticleEffect = ParticleEffect {
+    lifetime: 0.4..0.8,
+    speed: 80.0..220.0,
+    direction: FRAC_PI_2,
+    spread: PI,
+    gravity: 600.0,
+    start_color: Color::WHITE,
+    end_color: Color::srgba(1.0, 1.0, 1.0, 0.0),
+    start_size: 5.0,
+    end_size: 2.0,
+};
+
+/// Droplets thrown up where the bat hit the bottom
+pub static CRASH_SPLASH: ParticleEffect = ParticleEffect {
+    lifetime: 0.6..1.0,
+    speed: 250.0..500.0,
+    direction: FRAC_PI_2,
+    spread: 0.6,
+    gravity: 1500.0,
+    start_color: Color::srgba(0.4, 0.55, 0.7, 0.9),
+    end_color: Color::srgba(0.4, 0.55, 0.7, 0.0),
+    start_size: 6.0,
+    end_size: 2.0,
+};
+
+/// Fine spray hanging over the splash for a moment after the droplets
+pub static CRASH_MIST: ParticleEffect = ParticleEffect {
+    lifetime: 0.5..0.9,
+    speed: 30.0..90.0,
+    direction: FRAC_PI_2,
+    spread: 1.2,
+    gravity: 40.0,
+    start_color: Color::srgba(0.7, 0.8, 0.9, 0.4),
+    end_color: Color::srgba(0.7, 0.8, 0.9, 0.0),
+    start_size: 3.0,
+    end_size: 8.0,
+};
+
+/// When an emitter spawns its particles
+pub enum EmissionMode {
+    /// All at once, after which the emitter is removed
+    Burst(u32),
+    /// This many per second until the timer finishes, after which the emitter is removed
+    Continuous { rate: f32, duration: Timer },
+}
+
+/// Spawns particles of `effect` from its translation
+#[derive(Component)]
+#[require(Transform, DespawnOnReset)]
+pub struct ParticleEmitter {
+    effect: &'static ParticleEffect,
+    mode: EmissionMode,
+    start_color: Color,
+    end_color: Color,
+    /// Particles owed by a continuous emitter that have not spawned yet, carried over between frames
+    pending: f32,
+}
+
+impl ParticleEmitter {
+    pub fn burst(effect: &'static ParticleEffect, count: u32) -> Self {
+        Self::new(effect, EmissionMode::Burst(count))
+    }
+
+    pub fn continuous(effect: &'static ParticleEffect, rate: f32, secs: f32) -> Self {
+        Self::new(
+            effect,
+            EmissionMode::Continuous {
+                rate,
+                duration: Timer::from_seconds(secs, TimerMode::Once),
+            },
+        )
+    }
+
+    fn new(effect: &'static ParticleEffect, mode: EmissionMode) -> Self {
+        Self {
+            effect,
+            mode,
+            start_color: effect.start_color,
+            end_color: effect.end_color,
+            pending: 0.0,
+        }
+    }
+
+    /// Uses `color` instead of the effect's colors, fading out over the particle's life
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.start_color = color;
+        self.end_color = color.with_alpha(0.0);
+        self
+    }
+
+    /// Number of particles to spawn after `delta`, and whether the emitter is done
+    fn due(&mut self, delta: Duration) -> (u32, bool) {
+        match &mut self.mode {
+            EmissionMode::Burst(count) => (*count, true),
+            EmissionMode::Continuous { rate, duration } => {
+                duration.tick(delta);
+                self.pending += *rate * delta.as_secs_f32();
+                let count = self.pending.floor();
+                self.pending -= count;
+                (count as u32, duration.finished())
+            }
+        }
+    }
+
+    fn particle(&self, rng: &mut impl Rng) -> Particle {
+        let effect = self.effect;
+        let angle = effect.direction + effect.spread * rng.gen_range(-1.0..=1.0);
+        Particle {
+            velocity: Vec2::from_angle(angle) * pick(&effect.speed, rng),
+            life: Timer::from_seconds(pick(&effect.lifetime, rng), TimerMode::Once),
+            start_color: self.start_color,
+            end_color: self.end_color,
+            start_size: effect.start_size,
+            end_size: effect.end_size,
+        }
+    }
+}
+
+/// A random value in `range`, which is allowed to be empty
+fn pick(range: &Range<f32>, rng: &mut impl Rng) -> f32 {
+    range.start.lerp(range.end, rng.gen())
+}
+
+/// A single particle, moved by its own velocity rather than `Velocity`, since particles keep moving outside of
+/// gameplay such as after a crash
+#[derive(Component)]
+#[require(Sprite, Transform, DespawnOnReset)]
+struct Particle {
+    /// Logical pixels per second
+    velocity: Vec2,
+    life: Timer,
+    start_color: Color,
+    end_color: Color,
+    start_size: f32,
+    end_size: f32,
+}
+
+fn emit_wing_dust(mut commands: Commands, mut flaps: EventReader<PlayerFlapped>, player: Option<Single<&Transform, With<Player>>>) {
+    let Some(player) = player else {
+        flaps.clear();
+        return;
+    };
+    for _ in flaps.read() {
+        commands.spawn((
+            ParticleEmitter::burst(&WING_DUST, WING_DUST_COUNT),
+            Transform::from_translation(player.translation.with_z(PARTICLE_Z)),
+        ));
+    }
+}
+
+fn emit_insect_bits(mut commands: Commands, mut eaten: EventReader<EnemyEaten>, settings: Res<Settings>) {
+    for event in eaten.read() {
+        let color = event.species.sprite.palette_color(settings.colorblind_palette);
+        commands.spawn((
+            ParticleEmitter::burst(&INSECT_BITS, INSECT_BITS_COUNT).with_color(color),
+            Transform::from_translation(event.position.extend(PARTICLE_Z)),
+        ));
+    }
+}
+
+fn emit_crash_splash(mut commands: Commands, mut game_over: EventReader<GameOver>, player: Option<Single<&Transform, With<Player>>>) {
+    let crashed = game_over.read().filter(|event| *event.reason() == GameOverReason::Crashed).count() > 0;
+    let Some(player) = player.filter(|_| crashed) else {
+        return;
+    };
+    // The bat is already below the bottom by the time it counts as crashed
+    let transform = Transform::from_xyz(player.translation.x, WINDOW_BOTTOM, PARTICLE_Z);
+    commands.spawn((ParticleEmitter::burst(&CRASH_SPLASH, CRASH_SPLASH_COUNT), transform));
+    commands.spawn((
+        ParticleEmitter::continuous(&CRASH_MIST, CRASH_MIST_RATE, CRASH_MIST_SECS),
+        transform,
+    ));
+}
+
+fn emit_particles(mut commands: Commands, time: Res<Time>, mut emitters: Query<(Entity, &mut ParticleEmitter, &Transform)>) {
+    let mut rng = thread_rng();
+    for (entity, mut emitter, transform) in emitters.iter_mut() {
+        let (count, done) = emitter.due(time.delta());
+        for _ in 0..count {
+            let particle = emitter.particle(&mut rng);
+            let sprite = Sprite::from_color(particle.start_color, Vec2::splat(particle.start_size));
+            commands.spawn((particle, sprite, Gravity::new(emitter.effect.gravity), *transform));
+        }
+        if done {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Moves particles and fades them between their start and end looks, removing them at the end of their life
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite, Option<&Gravity>)>,
+) {
+    for (entity, mut particle, mut transform, mut sprite, gravity) in particles.iter_mut() {
+        particle.life.tick(time.delta());
+        if particle.life.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(gravity) = gravity {
+            particle.velocity.y += gravity.speed_change(time.delta());
+        }
+        transform.translation += particle.velocity.extend(0.0) * time.delta_secs();
+        let progress = particle.life.fraction();
+        sprite.color = particle.start_color.mix(&particle.end_color, progress);
+        sprite.custom_size = Some(Vec2::splat(particle.start_size.lerp(particle.end_size, progress)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{input_translation::GameInput, test_harness::TestGame};
+
+    #[test]
+    fn continuous_emitters_spread_particles_over_their_duration() {
+        let mut emitter = ParticleEmitter::continuous(&CRASH_MIST, 20.0, 1.0);
+        let steps: Vec<_> = (0..4).map(|_| emitter.due(Duration::from_secs_f32(0.25))).collect();
+        assert_eq!(steps, [(5, false), (5, false), (5, false), (5, true)]);
+    }
+
+    #[test]
+    fn flapping_throws_wing_dust() {
+        let mut game = TestGame::new();
+        game.send_input(GameInput::Flap);
+        game.advance_ticks(2);
+        let world = game.world_mut();
+        assert!(world.query::<&Particle>().iter(world).count() > 0);
+    }
+}
@@ -2,7 +2,11 @@ use std::ops::Not;
 
 use bevy::prelude::*;
 
-use crate::input_translation::GameInput;
+use crate::{
+    game::Reset,
+    input_translation::GameInput,
+    ui::{AcceptAction, BackAction, Focusable, Focused, MenuActionAppExt},
+};
 
 pub struct PauseMenuPlugin;
 
@@ -27,10 +31,21 @@ impl Not for PausedState {
 impl Plugin for PauseMenuPlugin {
     fn build(&self, app: &mut App) {
         app.insert_state::<PausedState>(PausedState::Unpaused);
-        app.add_systems(Update, check_for_pause);
+        app.add_event::<ContinueGame>();
+        app.add_menu_action::<ContinueGame>();
+        app.add_menu_action::<Reset>();
+        app.add_menu_action::<AppExit>();
+        app.add_systems(Startup, spawn_pause_menu);
+        app.add_systems(Update, (check_for_pause, continue_game.run_if(on_event::<ContinueGame>)));
+        app.add_systems(OnEnter(PausedState::Paused), enter_pause_menu);
+        app.add_systems(OnExit(PausedState::Paused), exit_pause_menu);
     }
 }
 
+/// Closes the pause menu and resumes the game
+#[derive(Event, Default, Clone)]
+pub struct ContinueGame;
+
 fn check_for_pause(
     mut event_reader: EventReader<GameInput>,
     current_state: Res<State<PausedState>>,
@@ -43,3 +58,75 @@ fn check_for_pause(
         }
     }
 }
+
+fn continue_game(mut next_state: ResMut<NextState<PausedState>>) {
+    next_state.set(PausedState::Unpaused);
+}
+
+fn enter_pause_menu(
+    mut commands: Commands,
+    menu_visible: Single<&mut Visibility, With<PauseMenu>>,
+    continue_button: Single<Entity, With<PauseContinueButton>>,
+) {
+    let mut visible = menu_visible.into_inner();
+    *visible = Visibility::Visible;
+    let entity = continue_button.into_inner();
+    commands.entity(entity).insert(Focused);
+}
+
+fn exit_pause_menu(mut commands: Commands, menu_visible: Single<&mut Visibility, With<PauseMenu>>, focused: Query<Entity, With<Focused>>) {
+    let mut visible = menu_visible.into_inner();
+    *visible = Visibility::Hidden;
+
+    for entity in focused.into_iter() {
+        commands.entity(entity).remove::<Focused>();
+    }
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    commands.spawn(PauseMenu).with_children(|parent| {
+        parent.spawn(PauseTitleText);
+        parent.spawn(PauseContinueButton);
+        parent.spawn(PauseRestartButton);
+        parent.spawn(PauseQuitButton);
+    });
+}
+
+#[derive(Component)]
+#[require(Text(|| Text::new("Paused")))]
+struct PauseTitleText;
+
+#[derive(Component)]
+#[require(AcceptAction<ContinueGame>, Focusable, Text(|| Text::new("Continue")))]
+struct PauseContinueButton;
+
+#[derive(Component)]
+#[require(AcceptAction<Reset>, Focusable, Text(|| Text::new("Restart")))]
+struct PauseRestartButton;
+
+#[derive(Component)]
+#[require(AcceptAction<AppExit>, Focusable, Text(|| Text::new("Quit")))]
+struct PauseQuitButton;
+
+#[derive(Component)]
+#[require(Node(Self::node), BackgroundColor(Self::background_color), Visibility(|| Visibility::Hidden), BackAction<ContinueGame>)]
+struct PauseMenu;
+
+impl PauseMenu {
+    fn node() -> Node {
+        Node {
+            width: Val::Percent(25.0),
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            align_self: AlignSelf::Center,
+            justify_self: JustifySelf::Center,
+            row_gap: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(16.0)),
+            ..default()
+        }
+    }
+    fn background_color() -> BackgroundColor {
+        BackgroundColor(Color::srgba(0.1, 0.1, 0.15, 0.9))
+    }
+}
@@ -0,0 +1,81 @@
+//! Reading and writing RON files in the platform's config and data directories
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Directory name used under the platform config and data directories
+const APP_DIRECTORY: &str = "bat-game-bevy";
+
+/// Path of a file in the platform config directory, e.g. `~/.config/bat-game-bevy/<file_name>`
+pub fn config_file(file_name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIRECTORY).join(file_name))
+}
+
+/// Path of a file in the platform data directory, e.g. `~/.local/share/bat-game-bevy/<file_name>`
+pub fn data_file(file_name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIRECTORY).join(file_name))
+}
+
+/// Reads `path`, logging why if the file is missing or invalid
+pub fn load<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            info!("Could not read {}: {err}", path.display());
+            return None;
+        }
+    };
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            error!("Could not parse {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Reads `path`, falling back to the default value if the file is missing or invalid
+pub fn load_or_default<T: DeserializeOwned + Default>(path: Option<PathBuf>) -> T {
+    let Some(path) = path else {
+        warn!("No platform directory available, using defaults");
+        return T::default();
+    };
+    load(&path).unwrap_or_default()
+}
+
+/// Writes `value` to `path` in a readable layout, creating parent directories as needed.
+/// Failures are logged rather than returned
+pub fn save<T: Serialize>(path: Option<PathBuf>, value: &T) {
+    write(path, ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()));
+}
+
+/// Writes `value` to `path` on as few lines as possible, for files that are too large to read by hand
+pub fn save_compact<T: Serialize>(path: Option<PathBuf>, value: &T) {
+    write(path, ron::to_string(value));
+}
+
+fn write(path: Option<PathBuf>, contents: ron::Result<String>) {
+    let Some(path) = path else {
+        warn!("No platform directory available, not saving");
+        return;
+    };
+    let contents = match contents {
+        Ok(contents) => contents,
+        Err(err) => {
+            error!("Could not serialize {}: {err}", path.display());
+            return;
+        }
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, contents));
+    match result {
+        Ok(()) => info!("Saved {}", path.display()),
+        Err(err) => error!("Could not write {}: {err}", path.display()),
+    }
+}
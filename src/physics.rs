@@ -2,7 +2,7 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use crate::constants::WINDOW_DIMENSIONS;
+use crate::{constants::WINDOW_DIMENSIONS, interpolation::Interpolated};
 
 #[derive(Component, Default)]
 pub struct WrappingMovement;
@@ -15,6 +15,15 @@ pub struct Gravity {
 
 impl Gravity {
     const DEFAULT_VALUE: f32 = 1500.0;
+
+    pub fn new(acceleration: f32) -> Self {
+        Self { acceleration }
+    }
+
+    /// Change in vertical speed over `time_delta`
+    pub fn speed_change(&self, time_delta: Duration) -> f32 {
+        -self.acceleration * time_delta.as_secs_f32()
+    }
 }
 
 impl Default for Gravity {
@@ -45,6 +54,7 @@ impl Default for Friction {
 
 /// Movement in logical pixels per second
 #[derive(Component, Default, Deref, DerefMut)]
+#[require(Interpolated)]
 pub struct Velocity(Vec2);
 
 impl Velocity {
@@ -55,7 +65,7 @@ impl Velocity {
 
     /// Decreases y velocity based on `Gravity`
     fn add_gravity(&mut self, gravity: &Gravity, time_delta: Duration) {
-        **self -= Vec2::new(0.0, gravity.acceleration * time_delta.as_secs_f32())
+        self.y += gravity.speed_change(time_delta);
     }
 }
 
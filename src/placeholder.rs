@@ -0,0 +1,170 @@
+//! Stand-in art for sprites whose image cannot be loaded, such as the bat strip which is not distributed
+//! with the source (see `assets/asset_distribution_note.md`).
+use std::f32::consts::TAU;
+
+use bevy::{
+    asset::{LoadState, RenderAssetUsages},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+/// Body and wing colors of the placeholder
+#[derive(Clone, Copy, Debug)]
+pub struct PlaceholderColors {
+    pub body: [u8; 4],
+    pub wing: [u8; 4],
+}
+
+impl PlaceholderColors {
+    const BAT: Self = Self {
+        body: [70, 40, 90, 255],
+        wing: [120, 80, 150, 255],
+    };
+    /// Pale, with see-through wings, so the sprite's color shows through
+    pub const TINTABLE: Self = Self {
+        body: [255, 255, 255, 255],
+        wing: [220, 220, 220, 150],
+    };
+}
+
+pub struct PlaceholderPlugin;
+impl Plugin for PlaceholderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, replace_missing_images);
+    }
+}
+
+/// Swaps this entity's sprite image for a generated atlas if the image fails to load.
+///
+/// Loading still waits on the image but carries on without it, as long as whatever the image belongs to is tracked
+/// with `LoadingAssets::track_with_optional_dependencies`
+#[derive(Component)]
+pub struct PlaceholderFallback {
+    /// Size of a single frame in pixels
+    frame_size: UVec2,
+    /// Columns and rows of frames in the atlas
+    grid: UVec2,
+    colors: PlaceholderColors,
+}
+
+impl PlaceholderFallback {
+    pub fn new(frame_size: UVec2, grid: UVec2) -> Self {
+        Self {
+            frame_size,
+            grid,
+            colors: PlaceholderColors::BAT,
+        }
+    }
+
+    pub fn with_colors(mut self, colors: PlaceholderColors) -> Self {
+        self.colors = colors;
+        self
+    }
+}
+
+fn replace_missing_images(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut query: Query<(Entity, &mut Sprite, &PlaceholderFallback)>,
+) {
+    for (entity, mut sprite, fallback) in query.iter_mut() {
+        match asset_server.get_load_state(sprite.image.id()) {
+            Some(LoadState::Failed(_)) => {
+                let path = sprite
+                    .image
+                    .path()
+                    .map_or_else(|| "unknown image".to_owned(), |path| path.to_string());
+                warn!("Using placeholder for missing image {path}");
+                sprite.image = images.add(placeholder_atlas(fallback.frame_size, fallback.grid, fallback.colors));
+                commands.entity(entity).remove::<PlaceholderFallback>();
+            }
+            Some(LoadState::Loaded) => {
+                commands.entity(entity).remove::<PlaceholderFallback>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Draws a flapping winged silhouette into every frame of an atlas laid out left to right, top to bottom
+pub fn placeholder_atlas(frame_size: UVec2, grid: UVec2, colors: PlaceholderColors) -> Image {
+    let size = frame_size * grid;
+    let frames = grid.element_product();
+    let mut data = vec![0; (size.element_product() * 4) as usize];
+    for frame in 0..frames {
+        let origin = UVec2::new(frame % grid.x, frame / grid.x) * frame_size;
+        // Wing tips go from raised to lowered and back over the whole strip
+        let wing_lift = (frame as f32 / frames as f32 * TAU).cos();
+        for y in 0..frame_size.y {
+            for x in 0..frame_size.x {
+                if let Some(color) = placeholder_pixel(frame_size.as_vec2(), Vec2::new(x as f32, y as f32), wing_lift, colors) {
+                    let pixel = origin + UVec2::new(x, y);
+                    let index = ((pixel.y * size.x + pixel.x) * 4) as usize;
+                    data[index..index + 4].copy_from_slice(&color);
+                }
+            }
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Color of a pixel within one frame, or `None` if it is transparent. `wing_lift` is between -1.0 and 1.0
+fn placeholder_pixel(frame_size: Vec2, pixel: Vec2, wing_lift: f32, colors: PlaceholderColors) -> Option<[u8; 4]> {
+    let center = frame_size / 2.0;
+    // Normalized so the frame spans -1.0 to 1.0, with y pointing up
+    let offset = Vec2::new(pixel.x + 0.5 - center.x, center.y - pixel.y - 0.5) / center;
+
+    let body = Vec2::new(offset.x / 0.2, offset.y / 0.35);
+    if body.length_squared() <= 1.0 {
+        return Some(colors.body);
+    }
+
+    // Each wing is a triangle from the shoulder out to a tip that moves with the flap
+    let reach = offset.x.abs();
+    if (0.15..=0.95).contains(&reach) {
+        let progress = (reach - 0.15) / 0.8;
+        let tip_height = wing_lift * 0.6;
+        let top = 0.2_f32.lerp(tip_height, progress);
+        let bottom = (-0.2_f32).lerp(tip_height - 0.1, progress);
+        if (top.min(bottom)..=top.max(bottom)).contains(&offset.y) {
+            return Some(colors.wing);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atlas_matches_grid() {
+        let frame_size = UVec2::splat(16);
+        let grid = UVec2::new(8, 1);
+        let image = placeholder_atlas(frame_size, grid, PlaceholderColors::BAT);
+        assert_eq!(image.size(), frame_size * grid);
+
+        // Every frame should have something drawn in it
+        let data = image.data;
+        for frame in 0..grid.x {
+            let drawn = (0..frame_size.y).any(|y| {
+                (0..frame_size.x).any(|x| {
+                    let index = ((y * frame_size.x * grid.x + frame * frame_size.x + x) * 4 + 3) as usize;
+                    data[index] > 0
+                })
+            });
+            assert!(drawn, "frame {frame} is empty");
+        }
+    }
+}
@@ -1,23 +1,39 @@
 use bevy::prelude::*;
 
 use crate::{
-    animation::{AnimationTimer, Direction},
+    animation::{AnimationClips, AnimationFinished, Animator, Direction},
+    collision::Collider,
     constants::WINDOW_BOTTOM,
-    game::Reset,
+    echolocation::EcholocationCooldown,
+    game::EnemyEaten,
     game_over::{GameOver, GameOverReason},
     input_translation::{DirectionalInput, GameInput},
+    loading::LoadingAssets,
     physics::{Friction, Gravity, Velocity, WrappingMovement},
+    sprite_sheet::AnimatedSheet,
 };
-/// Asset path
-const PLAYER_SPRITE_PATH: &str = "sprites/nf_batFlightStrip.png";
-const PLAYER_SPRITE_SIZE: UVec2 = UVec2::splat(64);
-const PLAYER_SPRITE_GRID: UVec2 = UVec2 { x: 8, y: 1 };
+/// Asset path of the bat's sprite sheet, which names its image and lays out its clips
+const PLAYER_SHEET_PATH: &str = "sprites/bat.sheet.ron";
+/// Radius of the bat's body, smaller than the sprite so wing tips don't count
+const PLAYER_COLLIDER_RADIUS: f32 = 20.0;
 
 //Physics
 const HORIZONTAL_ACCELERATION: f32 = 750.0;
 const MAX_HORIZONTAL_SPEED: f32 = 1000.0;
 const FLAP_VERTICAL_STRENGTH: f32 = 500.0;
 const FLAP_HORIZONTAL_STRENGTH: f32 = 400.0;
+/// Falling faster than this switches from gliding to diving
+const DIVE_SPEED: f32 = 350.0;
+
+/// Names of the bat's animation clips, as given in its sprite sheet
+mod clips {
+    pub const IDLE: &str = "idle";
+    pub const FLAP: &str = "flap";
+    pub const GLIDE: &str = "glide";
+    pub const DIVE: &str = "dive";
+    pub const EAT: &str = "eat";
+    pub const DEATH: &str = "death";
+}
 
 #[derive(Event, Default)]
 pub struct PlayerFlapped;
@@ -29,52 +45,74 @@ pub struct PlayerScreetched;
 #[require(
     Sprite,
     Velocity,
-    AnimationTimer(player_animation_timer),
+    Animator(player_animator),
     WrappingMovement,
     Friction,
     Gravity,
-    Direction
+    Direction,
+    Collider(|| Collider::circle(PLAYER_COLLIDER_RADIUS))
 )]
 pub struct Player;
-pub fn spawn_player(mut commands: Commands, asset_server: Res<AssetServer>, mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>) {
-    let texture = asset_server.load(PLAYER_SPRITE_PATH);
-    let atlas_layout = TextureAtlasLayout::from_grid(PLAYER_SPRITE_SIZE, PLAYER_SPRITE_GRID.x, PLAYER_SPRITE_GRID.y, None, None);
-    let atlas_layout_handle = texture_atlas_layouts.add(atlas_layout);
-    commands.spawn((
-        Player,
-        Sprite::from_atlas_image(
-            texture,
-            TextureAtlas {
-                layout: atlas_layout_handle,
-                index: 0,
-            },
-        ),
-    ));
+pub fn spawn_player(mut commands: Commands, asset_server: Res<AssetServer>, mut loading: ResMut<LoadingAssets>) {
+    let sheet = asset_server.load(PLAYER_SHEET_PATH);
+    // The sheet's image may not be distributed, in which case a placeholder stands in for it
+    loading.track_with_optional_dependencies(&sheet);
+    commands.spawn((Player, AnimatedSheet(sheet)));
 }
 
-fn player_animation_timer() -> AnimationTimer {
-    let frames = PLAYER_SPRITE_GRID.element_product();
-    const ANIMATION_SECS: f32 = 0.2;
-    AnimationTimer::new(frames, ANIMATION_SECS)
+/// Clips come from the sprite sheet once it has loaded
+fn player_animator() -> Animator {
+    Animator::new(AnimationClips::default(), clips::IDLE)
 }
 
 /// Resets a player to their original position, velocity, and animation frame
-pub fn reset_player(
-    mut reader: EventReader<Reset>,
-    query: Single<(&mut Transform, &mut Sprite, &mut AnimationTimer, &mut Velocity), With<Player>>,
+pub fn reset_player(query: Single<(&mut Transform, &mut Sprite, &mut Animator, &mut Velocity), With<Player>>) {
+    let (mut transform, mut sprite, mut animator, mut velocity) = query.into_inner();
+    transform.translation = Vec3::ZERO;
+    animator.restart(clips::IDLE);
+    // Intentionally not flipping x sprite based on direction. The atlas is missing until the sheet has loaded
+    if let Some(atlas) = sprite.texture_atlas.as_mut() {
+        atlas.index = animator.frame();
+    }
+    **velocity = Vec2::ZERO;
+}
+
+/// Picks the bat's clip from what it just did and how it is moving
+pub fn animate_player(
+    mut flaps: EventReader<PlayerFlapped>,
+    mut eaten: EventReader<EnemyEaten>,
+    mut game_over: EventReader<GameOver>,
+    mut finished: EventReader<AnimationFinished>,
+    player: Single<(Entity, &Velocity, &mut Animator), With<Player>>,
 ) {
-    if !reader.is_empty() {
-        let (mut transform, mut sprite, mut animation_timer, mut velocity) = query.into_inner();
-        transform.translation = Vec3::ZERO;
-        // Intentionally not flipping x sprite based on direction
-        match sprite.texture_atlas.as_mut() {
-            Some(atlas) => atlas.index = 0,
-            None => error!("Player sprite missing texture atlas"),
-        }
-        animation_timer.stop();
-        **velocity = Vec2::ZERO;
+    let (entity, velocity, mut animator) = player.into_inner();
+    // Read everything, so nothing left over triggers a clip on a later frame
+    let crashed = game_over.read().count() > 0;
+    let flapped = flaps.read().count() > 0;
+    let ate = eaten.read().count() > 0;
+    let clip_finished = finished
+        .read()
+        .filter(|event| event.entity == entity && event.clip == animator.current())
+        .count()
+        > 0;
+    if crashed {
+        animator.restart(clips::DEATH);
+    } else if flapped {
+        animator.restart(clips::FLAP);
+    } else if ate {
+        animator.restart(clips::EAT);
+    } else if let Some(clip) = movement_clip(animator.current(), clip_finished, velocity.y) {
+        animator.play(clip);
+    }
+}
 
-        reader.clear();
+/// Glide or dive once nothing else is playing. Flapping and eating play out first, and death is final
+fn movement_clip(current: &str, finished: bool, vertical_speed: f32) -> Option<&'static str> {
+    match current {
+        clips::DEATH => None,
+        clips::FLAP | clips::EAT if !finished => None,
+        _ if vertical_speed < -DIVE_SPEED => Some(clips::DIVE),
+        _ => Some(clips::GLIDE),
     }
 }
 
@@ -86,15 +124,20 @@ pub fn check_player_crashed(mut writer: EventWriter<GameOver>, query: Single<&Tr
 }
 
 //TODO determine if "Direction" is needless abstraction (It probably is tbh)
+/// Applies player input once per fixed tick.
+///
+/// Bevy keeps events until a fixed tick has run, so inputs sent on frames without a tick are read by the next one,
+/// and the reader's cursor stops frames with several ticks from reading them twice
 pub fn handle_input(
     mut reader: EventReader<GameInput>,
     mut screetch_writer: EventWriter<PlayerScreetched>,
     mut flap_writer: EventWriter<PlayerFlapped>,
     direction_input: Res<DirectionalInput>,
+    mut echolocation_cooldown: ResMut<EcholocationCooldown>,
     time: Res<Time>,
-    query: Single<(&mut Velocity, &mut AnimationTimer, &mut Direction), With<Player>>,
+    query: Single<(&mut Velocity, &mut Direction), With<Player>>,
 ) {
-    let (mut velocity, mut animation_timer, mut sprite_direction) = query.into_inner();
+    let (mut velocity, mut sprite_direction) = query.into_inner();
     let direction = direction_input.get_normalized();
     if direction < 0.0 {
         *sprite_direction = Direction::Backward;
@@ -110,13 +153,99 @@ pub fn handle_input(
             GameInput::Flap => {
                 velocity.y += FLAP_VERTICAL_STRENGTH;
                 velocity.x += direction * FLAP_HORIZONTAL_STRENGTH;
-                animation_timer.start();
                 flap_writer.send_default();
             }
             GameInput::Screetch => {
-                screetch_writer.send_default();
+                if echolocation_cooldown.ready() {
+                    echolocation_cooldown.trigger();
+                    screetch_writer.send_default();
+                }
             }
             _ => continue,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        animation::{AnimationClip, PlaybackMode},
+        test_harness::TestGame,
+        GameState,
+    };
+
+    #[test]
+    fn flap_lifts_player() {
+        let mut game = TestGame::new();
+        game.send_input(GameInput::Flap);
+        game.advance_ticks(1);
+        assert!(game.player_velocity().y > 0.0);
+        game.advance_ticks(5);
+        assert!(game.player_translation().y > 0.0);
+    }
+
+    #[test]
+    fn player_falls_without_input() {
+        let mut game = TestGame::new();
+        game.advance_ticks(30);
+        assert!(game.player_velocity().y < 0.0);
+        assert!(game.player_translation().y < 0.0);
+    }
+
+    #[test]
+    fn held_direction_moves_player() {
+        let mut game = TestGame::new();
+        game.hold_direction(-1.0);
+        game.advance_ticks(30);
+        assert!(game.player_velocity().x < 0.0);
+        assert!(game.player_translation().x < 0.0);
+    }
+
+    #[test]
+    fn flap_plays_out_before_gliding_or_diving() {
+        assert_eq!(movement_clip(clips::IDLE, false, 0.0), Some(clips::GLIDE));
+        assert_eq!(movement_clip(clips::FLAP, false, -1000.0), None);
+        assert_eq!(movement_clip(clips::FLAP, true, -1000.0), Some(clips::DIVE));
+        assert_eq!(movement_clip(clips::DEATH, true, 0.0), None);
+    }
+
+    #[test]
+    fn death_plays_out_after_crashing() {
+        let mut game = TestGame::new();
+        let world = game.world_mut();
+        let player = world.query_filtered::<Entity, With<Player>>().single(world);
+        // Clips are set by hand rather than waiting on the sheet, which would replace them once loaded
+        world.entity_mut(player).remove::<AnimatedSheet>();
+        let clips = AnimationClips::default()
+            .with(clips::GLIDE, AnimationClip::new(2..=2, 1.0, PlaybackMode::Loop).unwrap())
+            .with(clips::DEATH, AnimationClip::new(4..=7, 8.0, PlaybackMode::Once).unwrap());
+        world.get_mut::<Animator>(player).unwrap().set_clips(clips);
+
+        for _ in 0..120 {
+            if game.game_state() == GameState::Gameover {
+                break;
+            }
+            game.advance_ticks(1);
+        }
+        assert_eq!(game.game_state(), GameState::Gameover);
+        let animator = game.world_mut().get::<Animator>(player).unwrap();
+        assert_eq!(animator.current(), clips::DEATH);
+        let frame = animator.frame();
+        game.advance_ticks(30);
+        let animator = game.world_mut().get::<Animator>(player).unwrap();
+        assert!(animator.frame() > frame, "death clip didn't play in game over");
+    }
+
+    #[test]
+    fn falling_off_screen_ends_game() {
+        let mut game = TestGame::new();
+        game.record_events::<GameOver>();
+        game.advance_ticks(120);
+        assert_eq!(
+            game.recorded_events::<GameOver>().first(),
+            Some(&GameOver::new(GameOverReason::Crashed))
+        );
+        assert_eq!(game.game_state(), GameState::Gameover);
+    }
+}
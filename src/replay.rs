@@ -0,0 +1,187 @@
+//! Records the input of every run to a replay file, and plays a replay file back in place of device input.
+//!
+//! Start the game with `--replay <path>` to watch a replay, which can still be paused. Finished runs are saved to the
+//! `replays` folder in the platform data directory
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{GameRng, GameplaySystem, Reset, ResetSystem},
+    input_bindings::{InputBindings, InputSource},
+    input_translation::{DirectionalInput, GameInput, GameInputSystem, InputTranslationSystem},
+    pause_menu::PausedState,
+    persistence, GameState, InMenu,
+};
+
+const REPLAY_ARG: &str = "--replay";
+const REPLAY_DIRECTORY: &str = "replays";
+/// Increase whenever a gameplay change would make older replays play out differently
+const REPLAY_VERSION: u32 = 2;
+
+pub struct ReplayPlugin;
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayRecorder>();
+        if let Some(playback) = replay_path_from_args().and_then(|path| ReplayPlayback::load(&path)) {
+            app.insert_resource(playback);
+        }
+        app.configure_sets(PreUpdate, GameInputSystem.run_if(not(resource_exists::<ReplayPlayback>)));
+        app.add_systems(
+            PreUpdate,
+            translate_pause
+                .in_set(InputTranslationSystem)
+                .run_if(resource_exists::<ReplayPlayback>)
+                .run_if(not(in_state(InMenu))),
+        );
+        app.add_systems(
+            OnEnter(GameState::MainMenu),
+            start_playback.run_if(resource_exists::<ReplayPlayback>),
+        );
+        app.add_systems(
+            Update,
+            (
+                start_recording.run_if(not(resource_exists::<ReplayPlayback>)),
+                restart_playback.run_if(resource_exists::<ReplayPlayback>),
+            )
+                .after(ResetSystem)
+                .run_if(on_event::<Reset>),
+        );
+        app.add_systems(
+            FixedUpdate,
+            (
+                record_tick.run_if(not(resource_exists::<ReplayPlayback>)),
+                play_tick.run_if(resource_exists::<ReplayPlayback>),
+            )
+                .before(GameplaySystem)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PausedState::Unpaused)),
+        );
+        app.add_systems(
+            OnEnter(GameState::Gameover),
+            (save_recording.run_if(not(resource_exists::<ReplayPlayback>)), finish_playback),
+        );
+    }
+}
+
+/// Everything needed to play a run out again
+#[derive(Serialize, Deserialize)]
+struct Replay {
+    version: u32,
+    /// Seed of `GameRng` at the start of the run
+    seed: u64,
+    ticks: Vec<ReplayTick>,
+}
+
+/// Input for one fixed tick: normalized `DirectionalInput` and any `GameInput`s.
+///
+/// Kept as a tuple so the replay file stays small
+#[derive(Serialize, Deserialize)]
+struct ReplayTick(f32, Vec<GameInput>);
+
+/// The run being recorded, if any
+#[derive(Resource, Default)]
+struct ReplayRecorder {
+    replay: Option<Replay>,
+}
+
+/// A replay being played back. Device input for gameplay is ignored while this exists
+#[derive(Resource)]
+struct ReplayPlayback {
+    replay: Replay,
+    next_tick: usize,
+}
+
+impl ReplayPlayback {
+    fn load(path: &Path) -> Option<Self> {
+        let replay: Replay = persistence::load(path)?;
+        if replay.version != REPLAY_VERSION {
+            warn!(
+                "{} was recorded with replay version {}, but this build uses {REPLAY_VERSION}. It may not play out the same",
+                path.display(),
+                replay.version
+            );
+        }
+        info!("Playing back {} ({} ticks)", path.display(), replay.ticks.len());
+        Some(Self { replay, next_tick: 0 })
+    }
+}
+
+fn replay_path_from_args() -> Option<PathBuf> {
+    let mut args = std::env::args().skip_while(|arg| arg != REPLAY_ARG).skip(1);
+    let path = args.next();
+    if path.is_none() && std::env::args().any(|arg| arg == REPLAY_ARG) {
+        error!("{REPLAY_ARG} needs the path of a replay file");
+    }
+    path.map(PathBuf::from)
+}
+
+/// The one device input still used during playback, so a replay can be paused and left from the pause menu
+fn translate_pause(mut writer: EventWriter<GameInput>, bindings: Res<InputBindings>, sources: Res<ButtonInput<InputSource>>) {
+    let bound = bindings.game.get(&GameInput::Start).into_iter().flatten().copied();
+    if sources.any_just_pressed(bound) {
+        writer.send(GameInput::Start);
+    }
+}
+
+fn start_playback(mut writer: EventWriter<Reset>) {
+    writer.send(Reset::then(GameState::Playing));
+}
+
+/// Puts the game back into the state the replay was recorded from
+fn restart_playback(mut playback: ResMut<ReplayPlayback>, mut rng: ResMut<GameRng>) {
+    *rng = GameRng::with_seed(playback.replay.seed);
+    playback.next_tick = 0;
+}
+
+fn start_recording(mut recorder: ResMut<ReplayRecorder>, rng: Res<GameRng>) {
+    recorder.replay = Some(Replay {
+        version: REPLAY_VERSION,
+        seed: rng.seed(),
+        ticks: Vec::new(),
+    });
+}
+
+fn record_tick(mut recorder: ResMut<ReplayRecorder>, mut inputs: EventReader<GameInput>, direction: Res<DirectionalInput>) {
+    // Pausing is not part of the run
+    let inputs = inputs.read().copied().filter(|input| *input != GameInput::Start).collect();
+    if let Some(replay) = recorder.replay.as_mut() {
+        replay.ticks.push(ReplayTick(direction.get_normalized(), inputs));
+    }
+}
+
+/// Feeds the next tick's input to gameplay
+fn play_tick(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    mut writer: EventWriter<GameInput>,
+    mut direction: ResMut<DirectionalInput>,
+) {
+    let Some(ReplayTick(recorded_direction, inputs)) = playback.replay.ticks.get(playback.next_tick) else {
+        info!("Replay finished");
+        commands.remove_resource::<ReplayPlayback>();
+        return;
+    };
+    direction.set(*recorded_direction);
+    writer.send_batch(inputs.iter().copied());
+    playback.next_tick += 1;
+}
+
+fn save_recording(mut recorder: ResMut<ReplayRecorder>) {
+    let Some(replay) = recorder.replay.take() else {
+        return;
+    };
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+    let path = persistence::data_file(REPLAY_DIRECTORY).map(|dir| dir.join(format!("run-{timestamp}.ron")));
+    persistence::save_compact(path, &replay);
+}
+
+fn finish_playback(mut commands: Commands) {
+    commands.remove_resource::<ReplayPlayback>();
+}
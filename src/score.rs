@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+
+use crate::{
+    game::{EnemyEaten, GameplaySystem, ResetAppExt},
+    pause_menu::PausedState,
+    GameState,
+};
+
+/// Seconds after eating an enemy in which the next one continues the combo
+const COMBO_WINDOW_SECS: f32 = 2.0;
+/// Consecutive eats needed to raise the multiplier by one
+const COMBO_PER_MULTIPLIER: u32 = 3;
+const MAX_MULTIPLIER: u32 = 5;
+
+pub struct ScorePlugin;
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resetting_resource::<Score>();
+        app.add_systems(Startup, spawn_score_hud);
+        app.add_systems(
+            FixedUpdate,
+            (tick_combo, add_points.run_if(on_event::<EnemyEaten>))
+                .chain()
+                .in_set(ScoreSystem)
+                .run_if(in_state(GameState::Playing))
+                .run_if(in_state(PausedState::Unpaused)),
+        );
+        app.add_systems(Update, update_score_hud.run_if(resource_changed::<Score>));
+        app.add_systems(Update, show_score_hud.run_if(state_changed::<GameState>));
+        app.configure_sets(FixedUpdate, ScoreSystem.in_set(GameplaySystem));
+    }
+}
+
+#[derive(SystemSet, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct ScoreSystem;
+
+/// Points earned this run along with the current combo
+#[derive(Resource)]
+pub struct Score {
+    points: u32,
+    combo: u32,
+    combo_timer: Timer,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self {
+            points: 0,
+            combo: 0,
+            combo_timer: Timer::from_seconds(COMBO_WINDOW_SECS, TimerMode::Once),
+        }
+    }
+}
+
+impl Score {
+    pub fn points(&self) -> u32 {
+        self.points
+    }
+
+    /// Number of enemies eaten in a row, each within `COMBO_WINDOW_SECS` of the last
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    /// Factor applied to the points of the next enemy eaten
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.combo / COMBO_PER_MULTIPLIER).min(MAX_MULTIPLIER)
+    }
+
+    fn eat(&mut self, points: u32) {
+        self.points += points * self.multiplier();
+        self.combo += 1;
+        self.combo_timer.reset();
+    }
+}
+
+fn tick_combo(time: Res<Time>, mut score: ResMut<Score>) {
+    if score.combo == 0 {
+        return;
+    }
+    // Ticking the timer alone shouldn't count as a change, or the HUD would be rebuilt every frame
+    if score.bypass_change_detection().combo_timer.tick(time.delta()).finished() {
+        score.combo = 0;
+    }
+}
+
+fn add_points(mut events: EventReader<EnemyEaten>, mut score: ResMut<Score>) {
+    for event in events.read() {
+        score.eat(event.species.points);
+    }
+}
+
+fn update_score_hud(
+    score: Res<Score>,
+    points_text: Single<&mut Text, (With<ScorePointsText>, Without<ScoreComboText>)>,
+    combo_text: Single<(&mut Text, &mut Visibility), With<ScoreComboText>>,
+) {
+    let mut points_text = points_text.into_inner();
+    **points_text = format!("Score: {}", score.points());
+
+    let (mut combo_text, mut combo_visible) = combo_text.into_inner();
+    if score.combo() > 1 {
+        **combo_text = format!("Combo {} (x{})", score.combo(), score.multiplier());
+        *combo_visible = Visibility::Inherited;
+    } else {
+        *combo_visible = Visibility::Hidden;
+    }
+}
+
+/// Shows the score during a run and on the game over screen that follows it, and hides it on every other screen
+fn show_score_hud(state: Res<State<GameState>>, hud: Single<&mut Visibility, With<ScoreHud>>) {
+    let mut visibility = hud.into_inner();
+    *visibility = match state.get() {
+        GameState::Ready | GameState::Playing | GameState::Gameover => Visibility::Inherited,
+        GameState::Loading | GameState::MainMenu => Visibility::Hidden,
+    };
+}
+
+fn spawn_score_hud(mut commands: Commands) {
+    commands.spawn(ScoreHud).with_children(|parent| {
+        parent.spawn(ScorePointsText);
+        parent.spawn(ScoreComboText);
+    });
+}
+
+#[derive(Component)]
+#[require(Text(Self::text))]
+struct ScorePointsText;
+
+impl ScorePointsText {
+    fn text() -> Text {
+        Text::new("Score: 0")
+    }
+}
+
+#[derive(Component)]
+#[require(Text, Visibility(|| Visibility::Hidden))]
+struct ScoreComboText;
+
+/// Hidden until a run starts
+#[derive(Component)]
+#[require(Node(Self::node), Visibility(|| Visibility::Hidden))]
+struct ScoreHud;
+
+impl ScoreHud {
+    fn node() -> Node {
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TestGame;
+
+    #[test]
+    fn multiplier_grows_with_combo() {
+        let mut score = Score::default();
+        for _ in 0..COMBO_PER_MULTIPLIER {
+            score.eat(10);
+        }
+        assert_eq!(score.points(), 10 * COMBO_PER_MULTIPLIER);
+        assert_eq!(score.multiplier(), 2);
+        score.eat(10);
+        assert_eq!(score.points(), 10 * COMBO_PER_MULTIPLIER + 20);
+    }
+
+    #[test]
+    fn multiplier_capped() {
+        let mut score = Score::default();
+        for _ in 0..COMBO_PER_MULTIPLIER * MAX_MULTIPLIER * 2 {
+            score.eat(1);
+        }
+        assert_eq!(score.multiplier(), MAX_MULTIPLIER);
+    }
+
+    #[test]
+    fn hud_only_shown_around_a_run() {
+        fn hud_visibility(game: &mut TestGame) -> Visibility {
+            let world = game.world_mut();
+            *world.query_filtered::<&Visibility, With<ScoreHud>>().single(world)
+        }
+        let mut game = TestGame::new();
+        assert_eq!(hud_visibility(&mut game), Visibility::Inherited);
+
+        game.world_mut().resource_mut::<NextState<GameState>>().set(GameState::MainMenu);
+        game.advance_ticks(1);
+        assert_eq!(hud_visibility(&mut game), Visibility::Hidden);
+    }
+}
@@ -0,0 +1,104 @@
+//! Player options, saved to the config directory
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::persistence;
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // Normally inserted by `main` already, since the window is created from it
+        app.init_resource::<Settings>();
+        app.add_systems(
+            PostUpdate,
+            (save_settings, apply_window_settings).run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+        );
+    }
+}
+
+#[derive(Resource, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Volumes are between 0.0 and 1.0
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    /// Muting keeps the volume, so unmuting goes back to it
+    pub master_muted: bool,
+    pub sfx_muted: bool,
+    pub music_muted: bool,
+    pub fullscreen: bool,
+    /// Monitor used when fullscreen, in the order the platform lists them
+    pub monitor: usize,
+    pub vsync: bool,
+    /// Multiplier for camera shake and zoom punches, where 0.0 turns them off
+    pub screen_shake: f32,
+    /// Draws insects in colors that stay distinct with common kinds of color blindness
+    pub colorblind_palette: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            music_volume: 1.0,
+            master_muted: false,
+            sfx_muted: false,
+            music_muted: false,
+            // Windowed while developing, so the game doesn't take over the screen
+            fullscreen: !cfg!(debug_assertions),
+            monitor: 0,
+            vsync: true,
+            screen_shake: 1.0,
+            colorblind_palette: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the saved settings. Called before the app is built, so the window can be created with them
+    pub fn load() -> Self {
+        persistence::load_or_default(persistence::config_file(SETTINGS_FILE))
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        if self.fullscreen {
+            // Current and Primary do not work on Wayland
+            WindowMode::BorderlessFullscreen(MonitorSelection::Index(self.monitor))
+        } else {
+            WindowMode::Windowed
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    persistence::save(persistence::config_file(SETTINGS_FILE), settings.as_ref());
+}
+
+fn apply_window_settings(settings: Res<Settings>, window: Option<Single<&mut Window, With<PrimaryWindow>>>) {
+    let Some(mut window) = window else {
+        return;
+    };
+    let mode = settings.window_mode();
+    if window.mode != mode {
+        window.mode = mode;
+    }
+    let present_mode = settings.present_mode();
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+}
@@ -0,0 +1,241 @@
+use bevy::{prelude::*, window::Monitor};
+
+use crate::{
+    input_translation::{InputTranslationSystem, MenuInput},
+    main_menu::MenuScreen,
+    settings::Settings,
+    ui::{Focusable, Focused},
+};
+
+/// How much one press of left or right changes a volume or the screen shake
+const LEVEL_STEP: f32 = 0.1;
+
+pub struct SettingsMenuPlugin;
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuScreen::Settings), spawn_settings_screen);
+        // Runs on every screen so input from before the settings screen opened is never read once it has
+        app.add_systems(PreUpdate, change_setting.after(InputTranslationSystem));
+        app.add_systems(
+            Update,
+            update_setting_texts
+                .run_if(in_state(MenuScreen::Settings))
+                .run_if(resource_changed::<Settings>),
+        );
+    }
+}
+
+/// An option shown on the settings screen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum SettingKind {
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+    Fullscreen,
+    Monitor,
+    Vsync,
+    ScreenShake,
+    ColorblindPalette,
+}
+
+impl SettingKind {
+    const ALL: [SettingKind; 8] = [
+        SettingKind::MasterVolume,
+        SettingKind::SfxVolume,
+        SettingKind::MusicVolume,
+        SettingKind::Fullscreen,
+        SettingKind::Monitor,
+        SettingKind::Vsync,
+        SettingKind::ScreenShake,
+        SettingKind::ColorblindPalette,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SettingKind::MasterVolume => "Master Volume",
+            SettingKind::SfxVolume => "Effects Volume",
+            SettingKind::MusicVolume => "Music Volume",
+            SettingKind::Fullscreen => "Display",
+            SettingKind::Monitor => "Monitor",
+            SettingKind::Vsync => "VSync",
+            SettingKind::ScreenShake => "Screen Shake",
+            SettingKind::ColorblindPalette => "Colorblind Palette",
+        }
+    }
+
+    fn value(&self, settings: &Settings) -> String {
+        let on_off = |on: bool| if on { "On" } else { "Off" }.to_owned();
+        let percent = |level: f32| format!("{:.0}%", level * 100.0);
+        let volume = |level: f32, muted: bool| if muted { "Muted".to_owned() } else { percent(level) };
+        match self {
+            SettingKind::MasterVolume => volume(settings.master_volume, settings.master_muted),
+            SettingKind::SfxVolume => volume(settings.sfx_volume, settings.sfx_muted),
+            SettingKind::MusicVolume => volume(settings.music_volume, settings.music_muted),
+            SettingKind::Fullscreen => if settings.fullscreen { "Fullscreen" } else { "Windowed" }.to_owned(),
+            SettingKind::Monitor => (settings.monitor + 1).to_string(),
+            SettingKind::Vsync => on_off(settings.vsync),
+            SettingKind::ScreenShake => percent(settings.screen_shake),
+            SettingKind::ColorblindPalette => on_off(settings.colorblind_palette),
+        }
+    }
+
+    /// Moves the setting `step` places up or down. Toggles ignore the direction, and the monitor wraps around
+    fn adjust(&self, settings: &mut Settings, step: i32, monitor_count: usize) {
+        match self {
+            SettingKind::MasterVolume => step_level(&mut settings.master_volume, step),
+            SettingKind::SfxVolume => step_level(&mut settings.sfx_volume, step),
+            SettingKind::MusicVolume => step_level(&mut settings.music_volume, step),
+            SettingKind::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            SettingKind::Monitor => {
+                let count = monitor_count.max(1) as i32;
+                settings.monitor = (settings.monitor as i32 + step).rem_euclid(count) as usize;
+            }
+            SettingKind::Vsync => settings.vsync = !settings.vsync,
+            SettingKind::ScreenShake => step_level(&mut settings.screen_shake, step),
+            SettingKind::ColorblindPalette => settings.colorblind_palette = !settings.colorblind_palette,
+        }
+    }
+
+    /// Mutes or unmutes a volume, and otherwise moves the setting forward
+    fn accept(&self, settings: &mut Settings, monitor_count: usize) {
+        match self {
+            SettingKind::MasterVolume => settings.master_muted = !settings.master_muted,
+            SettingKind::SfxVolume => settings.sfx_muted = !settings.sfx_muted,
+            SettingKind::MusicVolume => settings.music_muted = !settings.music_muted,
+            _ => self.adjust(settings, 1, monitor_count),
+        }
+    }
+}
+
+/// Moves a level between 0.0 and 1.0 by whole steps, rounding so repeated steps don't drift
+fn step_level(level: &mut f32, step: i32) {
+    let steps = (*level / LEVEL_STEP).round() + step as f32;
+    *level = (steps * LEVEL_STEP).clamp(0.0, 1.0);
+}
+
+/// Left and right change the focused setting, accept mutes volumes and moves anything else forward
+fn change_setting(
+    mut events: EventReader<MenuInput>,
+    focused: Option<Single<&SettingRow, With<Focused>>>,
+    monitors: Query<(), With<Monitor>>,
+    mut settings: ResMut<Settings>,
+) {
+    for event in events.read() {
+        let Some(row) = focused.as_deref() else {
+            continue;
+        };
+        let monitor_count = monitors.iter().len();
+        match event {
+            MenuInput::Left => row.0.adjust(&mut settings, -1, monitor_count),
+            MenuInput::Right => row.0.adjust(&mut settings, 1, monitor_count),
+            MenuInput::Accept => row.0.accept(&mut settings, monitor_count),
+            _ => {}
+        }
+    }
+}
+
+fn setting_text(kind: SettingKind, settings: &Settings) -> String {
+    format!("{}: < {} >", kind.label(), kind.value(settings))
+}
+
+fn update_setting_texts(settings: Res<Settings>, mut rows: Query<(&SettingRow, &mut Text)>) {
+    for (row, mut text) in rows.iter_mut() {
+        **text = setting_text(row.0, &settings);
+    }
+}
+
+fn spawn_settings_screen(mut commands: Commands, settings: Res<Settings>) {
+    commands.spawn(SettingsScreen).with_children(|parent| {
+        parent.spawn(SettingsTitleText);
+        for (index, kind) in SettingKind::ALL.into_iter().enumerate() {
+            let mut row = parent.spawn((SettingRow(kind), Text::new(setting_text(kind, &settings))));
+            if index == 0 {
+                row.insert(Focused);
+            }
+        }
+    });
+}
+
+/// Shows the current value of a setting, and changes it with left, right and accept
+#[derive(Component)]
+#[require(Focusable, Node(Self::node))]
+struct SettingRow(SettingKind);
+
+impl SettingRow {
+    fn node() -> Node {
+        Node {
+            width: Val::Percent(100.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+#[require(Text(|| Text::new("Settings")), TextFont(Self::font))]
+struct SettingsTitleText;
+
+impl SettingsTitleText {
+    fn font() -> TextFont {
+        TextFont {
+            font_size: 48.0,
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+#[require(Node(Self::node), BackgroundColor(Self::background_color), StateScoped<MenuScreen>(|| StateScoped(MenuScreen::Settings)))]
+struct SettingsScreen;
+
+impl SettingsScreen {
+    fn node() -> Node {
+        Node {
+            width: Val::Percent(40.0),
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            align_self: AlignSelf::Center,
+            justify_self: JustifySelf::Center,
+            row_gap: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(24.0)),
+            ..default()
+        }
+    }
+    fn background_color() -> BackgroundColor {
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.85))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_step_and_clamp() {
+        let mut settings = Settings::default();
+        SettingKind::MasterVolume.adjust(&mut settings, 1, 1);
+        assert_eq!(settings.master_volume, 1.0);
+        for _ in 0..3 {
+            SettingKind::MasterVolume.adjust(&mut settings, -1, 1);
+        }
+        assert_eq!(SettingKind::MasterVolume.value(&settings), "70%");
+        for _ in 0..20 {
+            SettingKind::MasterVolume.adjust(&mut settings, -1, 1);
+        }
+        assert_eq!(settings.master_volume, 0.0);
+
+        SettingKind::SfxVolume.accept(&mut settings, 1);
+        assert_eq!(SettingKind::SfxVolume.value(&settings), "Muted");
+        assert_eq!(settings.sfx_volume, 1.0);
+    }
+
+    #[test]
+    fn monitor_wraps() {
+        let mut settings = Settings::default();
+        SettingKind::Monitor.adjust(&mut settings, -1, 3);
+        assert_eq!(settings.monitor, 2);
+        SettingKind::Monitor.adjust(&mut settings, 1, 3);
+        assert_eq!(settings.monitor, 0);
+    }
+}
@@ -0,0 +1,189 @@
+//! Sprite sheets described in `.sheet.ron` files, so art can be swapped without code changes.
+//!
+//! In debug builds started with `--hot-reload`, Bevy's file watcher reloads sheets and their images whenever their files
+//! change on disk, and sprites using a sheet pick up its new layout and clips
+use std::{collections::BTreeMap, error::Error};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    animation::{AnimationClip, AnimationClips, Animator, PlaybackMode},
+    placeholder::PlaceholderFallback,
+};
+
+pub struct SpriteSheetPlugin;
+impl Plugin for SpriteSheetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<SpriteSheet>();
+        app.register_asset_loader(SpriteSheetLoader);
+        app.add_systems(Update, apply_sprite_sheets);
+    }
+}
+
+/// A loaded sheet: its image, the layout of its cells and the clips drawn from them
+#[derive(Asset, TypePath)]
+pub struct SpriteSheet {
+    #[dependency]
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub cell_size: UVec2,
+    pub grid: UVec2,
+    pub clips: AnimationClips,
+}
+
+/// Contents of a `.sheet.ron` file
+#[derive(Deserialize)]
+struct SpriteSheetDefinition {
+    /// Asset path of the image, from the assets directory
+    image: String,
+    /// Size of a single cell in pixels
+    cell_size: UVec2,
+    /// Columns and rows of cells
+    grid: UVec2,
+    /// Gap between neighbouring cells in pixels
+    #[serde(default)]
+    padding: UVec2,
+    /// Gap before the first cell in pixels
+    #[serde(default)]
+    offset: UVec2,
+    #[serde(default)]
+    clips: BTreeMap<String, ClipDefinition>,
+}
+
+#[derive(Deserialize)]
+struct ClipDefinition {
+    /// Indices of the first and last cells, counted left to right then top to bottom
+    frames: (usize, usize),
+    fps: f32,
+    mode: PlaybackMode,
+}
+
+impl SpriteSheetDefinition {
+    /// Fails if a clip has frames outside the grid or a frame rate that isn't positive
+    fn animation_clips(&self) -> Result<AnimationClips, String> {
+        let cells = self.grid.element_product() as usize;
+        self.clips.iter().try_fold(AnimationClips::default(), |clips, (name, clip)| {
+            let (first, last) = clip.frames;
+            if first > last || last >= cells {
+                return Err(format!(
+                    "clip \"{name}\" has frames {first} to {last}, outside the grid of {cells} cells"
+                ));
+            }
+            if !clip.fps.is_finite() {
+                return Err(format!("clip \"{name}\" has a frame rate of {}", clip.fps));
+            }
+            let animation_clip = AnimationClip::new(first..=last, clip.fps, clip.mode)
+                .map_err(|_| format!("clip \"{name}\" has a frame rate of {}, which must be above 0", clip.fps))?;
+            Ok(clips.with(name, animation_clip))
+        })
+    }
+}
+
+#[derive(Default)]
+struct SpriteSheetLoader;
+
+impl AssetLoader for SpriteSheetLoader {
+    type Asset = SpriteSheet;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    async fn load(&self, reader: &mut dyn Reader, _settings: &(), load_context: &mut LoadContext<'_>) -> Result<SpriteSheet, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let definition: SpriteSheetDefinition = ron::de::from_bytes(&bytes)?;
+
+        let layout = TextureAtlasLayout::from_grid(
+            definition.cell_size,
+            definition.grid.x,
+            definition.grid.y,
+            Some(definition.padding),
+            Some(definition.offset),
+        );
+        let clips = definition.animation_clips()?;
+        Ok(SpriteSheet {
+            image: load_context.load(definition.image),
+            layout: load_context.add_labeled_asset("layout".to_owned(), layout),
+            cell_size: definition.cell_size,
+            grid: definition.grid,
+            clips,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sheet.ron"]
+    }
+}
+
+/// Draws this entity from a sprite sheet, picking up the sheet again whenever it is reloaded
+#[derive(Component)]
+#[require(Sprite)]
+pub struct AnimatedSheet(pub Handle<SpriteSheet>);
+
+/// Gives entities their sheet's image, layout and clips once it has loaded, and again after every reload
+fn apply_sprite_sheets(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<SpriteSheet>>,
+    sheets: Res<Assets<SpriteSheet>>,
+    mut query: Query<(Entity, Ref<AnimatedSheet>, &mut Sprite, &mut Animator)>,
+) {
+    let mut loaded = Vec::new();
+    for event in events.read() {
+        if let AssetEvent::Added { id } | AssetEvent::Modified { id } = event {
+            loaded.push(*id);
+        }
+    }
+    for (entity, animated_sheet, mut sprite, mut animator) in query.iter_mut() {
+        if !animated_sheet.is_added() && !loaded.contains(&animated_sheet.0.id()) {
+            continue;
+        }
+        let Some(sheet) = sheets.get(&animated_sheet.0) else {
+            continue;
+        };
+        animator.set_clips(sheet.clips.clone());
+        sprite.image = sheet.image.clone();
+        sprite.texture_atlas = Some(TextureAtlas {
+            layout: sheet.layout.clone(),
+            index: animator.frame(),
+        });
+        commands
+            .entity(entity)
+            .insert(PlaceholderFallback::new(sheet.cell_size, sheet.grid));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn bat_sheet_clips_are_valid() {
+        let text = fs::read_to_string("assets/sprites/bat.sheet.ron").unwrap();
+        let definition: SpriteSheetDefinition = ron::from_str(&text).unwrap();
+        if let Err(err) = definition.animation_clips() {
+            panic!("{err}");
+        }
+    }
+
+    #[test]
+    fn invalid_clips_are_rejected() {
+        let sheet = |clip: &str| {
+            let text = format!("(image: \"sheet.png\", cell_size: (8, 8), grid: (4, 1), clips: {{ \"clip\": {clip} }})");
+            ron::from_str::<SpriteSheetDefinition>(&text).unwrap().animation_clips()
+        };
+        assert!(sheet("(frames: (0, 3), fps: 10.0, mode: Loop)").is_ok());
+        assert!(sheet("(frames: (2, 4), fps: 10.0, mode: Loop)").is_err());
+        assert!(sheet("(frames: (3, 1), fps: 10.0, mode: Loop)").is_err());
+        for fps in ["0.0", "-4.0", "NaN", "inf"] {
+            assert!(
+                sheet(&format!("(frames: (0, 3), fps: {fps}, mode: Loop)")).is_err(),
+                "fps {fps} was accepted"
+            );
+        }
+    }
+}
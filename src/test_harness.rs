@@ -0,0 +1,153 @@
+//! Runs the game's own plugins without a window, audio or rendering, so tests can play it one fixed tick at a time
+use bevy::{
+    asset::AssetPlugin, gizmos::GizmoPlugin, input::InputPlugin, prelude::*, render::render_resource::Shader, state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+
+use crate::{
+    collision::CollisionPlugin,
+    echolocation::EcholocationPlugin,
+    enemy::EnemyPlugin,
+    game::GamePlugin,
+    game_over::GameOverPlugin,
+    input_bindings::{InputBindings, InputCapture, InputSource},
+    input_translation::{DirectionalInput, GameInput, InputTranslationPlugin, InputTranslationSystem},
+    interpolation::InterpolationPlugin,
+    lighting::LightingPlugin,
+    loading::LoadingPlugin,
+    particles::ParticlePlugin,
+    pause_menu::PauseMenuPlugin,
+    physics::Velocity,
+    player::Player,
+    score::ScorePlugin,
+    settings::Settings,
+    sprite_sheet::SpriteSheetPlugin,
+    GameState, InMenu,
+};
+
+/// A game in `GameState::Playing` that only moves forward when told to
+pub struct TestGame {
+    app: App,
+}
+
+impl TestGame {
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, AssetPlugin::default(), InputPlugin));
+        // Gizmos are drawn by gameplay code, and need somewhere to put their shaders even though nothing renders
+        app.init_asset::<Shader>();
+        app.add_plugins(GizmoPlugin);
+        app.init_asset::<Image>();
+        app.init_asset::<TextureAtlasLayout>();
+        // Bindings and settings are left at their defaults rather than loaded from the player's config
+        app.init_resource::<InputBindings>();
+        app.init_resource::<InputCapture>();
+        app.init_resource::<ButtonInput<InputSource>>();
+        app.init_resource::<Settings>();
+        app.insert_state(GameState::Playing);
+        app.add_computed_state::<InMenu>();
+        app.add_plugins((
+            LoadingPlugin,
+            SpriteSheetPlugin,
+            InputTranslationPlugin,
+            PauseMenuPlugin,
+            GameOverPlugin,
+        ));
+        app.add_plugins((
+            GamePlugin,
+            EnemyPlugin,
+            CollisionPlugin,
+            InterpolationPlugin,
+            ScorePlugin,
+            EcholocationPlugin,
+            LightingPlugin,
+            ParticlePlugin,
+        ));
+
+        // Every update advances time by exactly one tick
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+        app.init_resource::<TickCount>();
+        app.init_resource::<HeldDirection>();
+        app.add_systems(FixedFirst, count_tick);
+        app.add_systems(PreUpdate, hold_direction.after(InputTranslationSystem));
+
+        // Runs startup, which takes no time, so no ticks happen yet
+        app.update();
+        Self { app }
+    }
+
+    /// Sent before the next tick, as if the player pressed it
+    pub fn send_input(&mut self, input: GameInput) {
+        self.app.world_mut().send_event(input);
+    }
+
+    /// Keeps `DirectionalInput` at `direction` every frame until changed
+    pub fn hold_direction(&mut self, direction: f32) {
+        self.app.world_mut().resource_mut::<HeldDirection>().0 = direction;
+    }
+
+    pub fn advance_ticks(&mut self, ticks: u32) {
+        let target = self.app.world().resource::<TickCount>().0 + ticks;
+        while self.app.world().resource::<TickCount>().0 < target {
+            self.app.update();
+        }
+    }
+
+    /// Collects every `E` sent from now on, for `recorded_events`
+    pub fn record_events<E: Event + Clone>(&mut self) {
+        self.app.init_resource::<RecordedEvents<E>>();
+        self.app.add_systems(Last, record_event::<E>);
+    }
+
+    pub fn recorded_events<E: Event + Clone>(&self) -> &[E] {
+        &self.app.world().resource::<RecordedEvents<E>>().0
+    }
+
+    pub fn player_translation(&mut self) -> Vec3 {
+        self.player::<Transform>().translation
+    }
+
+    pub fn player_velocity(&mut self) -> Vec2 {
+        **self.player::<Velocity>()
+    }
+
+    pub fn game_state(&self) -> GameState {
+        *self.app.world().resource::<State<GameState>>().get()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    fn player<C: Component>(&mut self) -> &C {
+        self.app.world_mut().query_filtered::<&C, With<Player>>().single(self.app.world())
+    }
+}
+
+#[derive(Resource, Default)]
+struct TickCount(u32);
+
+#[derive(Resource, Default)]
+struct HeldDirection(f32);
+
+#[derive(Resource)]
+struct RecordedEvents<E>(Vec<E>);
+
+impl<E> Default for RecordedEvents<E> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+fn count_tick(mut count: ResMut<TickCount>) {
+    count.0 += 1;
+}
+
+fn hold_direction(held: Res<HeldDirection>, mut direction: ResMut<DirectionalInput>) {
+    direction.set(held.0);
+}
+
+fn record_event<E: Event + Clone>(mut events: EventReader<E>, mut recorded: ResMut<RecordedEvents<E>>) {
+    recorded.0.extend(events.read().cloned());
+}
@@ -1,6 +1,8 @@
+use std::marker::PhantomData;
+
 use bevy::prelude::*;
 
-use crate::input_translation::MenuInput;
+use crate::input_translation::{InputTranslationSystem, MenuInput};
 
 #[derive(Component)]
 pub struct AcceptAction<T>(T);
@@ -9,6 +11,10 @@ impl<T> AcceptAction<T>
 where
     T: Event + Clone,
 {
+    pub fn new(event: T) -> Self {
+        Self(event)
+    }
+
     fn send_with(&self, writer: &mut EventWriter<T>) {
         writer.send(self.0.clone());
     }
@@ -29,12 +35,139 @@ pub fn accept_action<T>(
 ) where
     T: Event + Clone,
 {
-    if let Some(focused) = focus_query {
-        let accept_action = focused.into_inner();
-        for event in events.read() {
-            if *event == MenuInput::Accept {
-                accept_action.send_with(&mut writer);
+    // Always read, so an accept sent while nothing was focused isn't picked up once something is
+    for event in events.read() {
+        if let (MenuInput::Accept, Some(focused)) = (event, focus_query.as_deref()) {
+            focused.send_with(&mut writer);
+        }
+    }
+}
+
+/// Sends `T` when `MenuInput::Back` is received while this entity is visible
+#[derive(Component)]
+pub struct BackAction<T>(T);
+
+impl<T: Default> Default for BackAction<T> {
+    fn default() -> Self {
+        Self(T::default())
+    }
+}
+
+pub fn back_action<T>(mut events: EventReader<MenuInput>, query: Query<(&BackAction<T>, &InheritedVisibility)>, mut writer: EventWriter<T>)
+where
+    T: Event + Clone,
+{
+    for event in events.read() {
+        if *event != MenuInput::Back {
+            continue;
+        }
+        for (back_action, visibility) in query.iter() {
+            if visibility.get() {
+                writer.send(back_action.0.clone());
             }
         }
     }
 }
+
+/// UI node that can receive `Focused` through menu navigation
+#[derive(Component, Default)]
+#[require(Node, BackgroundColor(Self::background_color))]
+pub struct Focusable;
+
+impl Focusable {
+    const COLOR: Color = Color::srgb(0.55, 0.3, 0.1);
+    const FOCUSED_COLOR: Color = Color::srgb(0.95, 0.75, 0.35);
+
+    fn background_color() -> BackgroundColor {
+        BackgroundColor(Self::COLOR)
+    }
+}
+
+/// Registers the systems that send `T` from `AcceptAction<T>` and `BackAction<T>`
+struct MenuActionPlugin<T>(PhantomData<T>);
+
+impl<T> Plugin for MenuActionPlugin<T>
+where
+    T: Event + Clone,
+{
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, (accept_action::<T>, back_action::<T>).after(InputTranslationSystem));
+    }
+}
+
+pub trait MenuActionAppExt {
+    /// Allows `AcceptAction<T>` and `BackAction<T>` to send `T`. Safe to call from several plugins
+    fn add_menu_action<T: Event + Clone>(&mut self) -> &mut Self;
+}
+
+impl MenuActionAppExt for App {
+    fn add_menu_action<T: Event + Clone>(&mut self) -> &mut Self {
+        if !self.is_plugin_added::<MenuActionPlugin<T>>() {
+            self.add_plugins(MenuActionPlugin::<T>(PhantomData));
+        }
+        self
+    }
+}
+
+pub struct FocusPlugin;
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, navigate_focus.after(InputTranslationSystem));
+        app.add_systems(PostUpdate, highlight_focused);
+    }
+}
+
+/// Moves `Focused` to the nearest visible `Focusable` in the direction of the menu input.
+///
+/// Works on screen positions, so rows, columns and grids all navigate the way they look
+fn navigate_focus(
+    mut commands: Commands,
+    mut events: EventReader<MenuInput>,
+    focusables: Query<(Entity, &GlobalTransform, &InheritedVisibility, Has<Focused>), With<Focusable>>,
+) {
+    for event in events.read() {
+        let direction = match event {
+            // UI coordinates grow downwards
+            MenuInput::Up => Vec2::NEG_Y,
+            MenuInput::Down => Vec2::Y,
+            MenuInput::Left => Vec2::NEG_X,
+            MenuInput::Right => Vec2::X,
+            MenuInput::Accept | MenuInput::Back => continue,
+        };
+        let visible = || focusables.iter().filter(|(_, _, visibility, _)| visibility.get());
+
+        let Some((focused, origin)) = visible()
+            .find(|(_, _, _, focused)| *focused)
+            .map(|(entity, transform, _, _)| (entity, transform.translation().truncate()))
+        else {
+            // Nothing focused yet, so start on the first visible node
+            if let Some((entity, _, _, _)) = visible().next() {
+                commands.entity(entity).insert(Focused);
+            }
+            continue;
+        };
+
+        let target = visible()
+            .filter(|(entity, _, _, _)| *entity != focused)
+            .filter_map(|(entity, transform, _, _)| {
+                let offset = transform.translation().truncate() - origin;
+                let along = offset.dot(direction);
+                // Prefer nodes in line with the current one over closer ones off to the side
+                let across = offset.perp_dot(direction).abs();
+                (along > 0.0).then_some((entity, along + 2.0 * across))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((entity, _)) = target {
+            commands.entity(focused).remove::<Focused>();
+            commands.entity(entity).insert(Focused);
+        }
+    }
+}
+
+fn highlight_focused(mut query: Query<(&mut BackgroundColor, Has<Focused>), With<Focusable>>) {
+    for (mut background_color, focused) in query.iter_mut() {
+        let color = if focused { Focusable::FOCUSED_COLOR } else { Focusable::COLOR };
+        background_color.set_if_neq(BackgroundColor(color));
+    }
+}